@@ -9,4 +9,26 @@ pub enum PassportError {
     InvalidPoints = 4,
     Overflow = 5,
     TooManyVerifications = 6,
+    IssuerNotRegistered = 7,
+    ScopeNotGranted = 8,
+    NoPendingAdmin = 9,
+    VerificationNotFound = 10,
+    InvalidStatusTransition = 11,
+    InvalidConfig = 12,
+    MigrationRequired = 13,
+    InvalidBatchSize = 14,
+    SchemaTooNew = 15,
+    InvalidExpiry = 16,
+    UnknownIssuerKey = 17,
+    InvalidAttestation = 18,
+    InvalidNonce = 19,
+    KeyAlreadyRegistered = 20,
+    InvalidPublicKey = 21,
+    InvalidRequirement = 22,
+    FlagNotFound = 23,
+    TooManyFlags = 24,
+    Paused = 25,
+    QuorumNotReached = 26,
+    CheckpointUnavailable = 27,
+    NonTransferable = 28,
 }
@@ -1,84 +1,1507 @@
 #![no_std]
 
 pub mod errors;
+mod migration;
+mod requirements;
+mod scoring;
 pub mod types;
+mod webauthn;
 
 use errors::PassportError;
+use migration::{require_current_schema, require_known_schema, SCHEMA_VERSION};
 use types::*;
 
 use soroban_sdk::{
-    contract, contractimpl, panic_with_error, Address, Env, String, Symbol, Vec,
+    contract, contractimpl, panic_with_error, token::TokenInterface, xdr::FromXdr, Address, Bytes,
+    BytesN, Env, Map, MuxedAddress, String, Symbol, Vec,
 };
 
 // Constants
+// Code version, bumped on every release. The storage layout is versioned
+// separately by `migration::SCHEMA_VERSION`.
+const CONTRACT_VERSION: u32 = 7;
 const MAX_VERIFICATIONS_PER_USER: u32 = 50;
+const MAX_FLAGS_PER_USER: u32 = 20;
+const MAX_QUORUM: u32 = 10;
+const MAX_HISTORY_PER_USER: u32 = 100;
+const MAX_CHECKPOINTS_PER_USER: u32 = 100;
+
+// TTL defaults, in ledgers (~5s each)
+const DAY_IN_LEDGERS: u32 = 17_280;
+const DEFAULT_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const DEFAULT_TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
 
 // Helper functions
+fn read_admin(env: &Env) -> Address {
+    env.storage().instance().get(&DataKey::Admin).unwrap()
+}
+
+fn require_admin(env: &Env) -> Address {
+    require_known_schema(env);
+    let admin = read_admin(env);
+    admin.require_auth();
+    admin
+}
+
+// Writes in `switch` are stopped by the global pause or by their own switch.
+fn is_paused(env: &Env, switch: &PauseSwitch) -> bool {
+    let storage = env.storage().instance();
+    storage.has(&DataKey::Paused) || storage.has(&DataKey::PauseSwitch(switch.clone()))
+}
+
+fn require_not_paused(env: &Env, switch: PauseSwitch) {
+    if is_paused(env, &switch) {
+        panic_with_error!(env, PassportError::Paused);
+    }
+}
+
+fn is_registered_issuer(env: &Env, issuer: &Address) -> bool {
+    env.storage().instance().has(&DataKey::Issuer(issuer.clone()))
+}
+
+fn has_scope(env: &Env, issuer: &Address, vtype: &VerificationType) -> bool {
+    let key = DataKey::IssuerScope(issuer.clone(), vtype.clone());
+    env.storage().instance().has(&key)
+}
+
+fn read_scopes(env: &Env, issuer: &Address) -> Vec<VerificationType> {
+    let key = DataKey::IssuerScopes(issuer.clone());
+    env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
+}
+
+fn write_scopes(env: &Env, issuer: &Address, scopes: &Vec<VerificationType>) {
+    let key = DataKey::IssuerScopes(issuer.clone());
+    if scopes.is_empty() {
+        env.storage().instance().remove(&key);
+    } else {
+        env.storage().instance().set(&key, scopes);
+    }
+}
+
+fn read_issuer_keys(env: &Env, issuer: &Address) -> Vec<BytesN<32>> {
+    let key = DataKey::IssuerKeys(issuer.clone());
+    env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(env))
+}
+
+fn write_issuer_keys(env: &Env, issuer: &Address, keys: &Vec<BytesN<32>>) {
+    let key = DataKey::IssuerKeys(issuer.clone());
+    if keys.is_empty() {
+        env.storage().instance().remove(&key);
+    } else {
+        env.storage().instance().set(&key, keys);
+    }
+}
+
+fn read_point_table(env: &Env) -> Map<VerificationType, PointRule> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PointTable)
+        .unwrap_or_else(|| Map::new(env))
+}
+
+// Points allowed by the table. Types without a rule accept any positive value.
+fn points_allowed(rule: &Option<PointRule>, points: i32) -> bool {
+    match rule {
+        Some(PointRule::Fixed(p)) => points == *p,
+        Some(PointRule::Range(min, max)) => *min <= points && points <= *max,
+        None => points > 0,
+    }
+}
+
+// Closest value the rule allows. Scores count stored points through it, so
+// table changes apply to existing verifications without rewriting them.
+fn conform_points(rule: &Option<PointRule>, points: i32) -> i32 {
+    match rule {
+        Some(PointRule::Fixed(p)) => *p,
+        Some(PointRule::Range(min, max)) => points.clamp(*min, *max),
+        None => points,
+    }
+}
+
+// Identifier attestations refer to a key by: the public key itself for
+// ed25519, its sha256 for secp256r1 (SEC1 uncompressed, 65 bytes).
+fn issuer_key_id(env: &Env, algorithm: &KeyAlgorithm, public_key: &Bytes) -> BytesN<32> {
+    match algorithm {
+        KeyAlgorithm::Ed25519 => BytesN::try_from(public_key.clone()).unwrap_or_else(|_| {
+            panic_with_error!(env, PassportError::InvalidPublicKey);
+        }),
+        KeyAlgorithm::Secp256r1 => {
+            if public_key.len() != 65 || public_key.get(0) != Some(0x04) {
+                panic_with_error!(env, PassportError::InvalidPublicKey);
+            }
+            env.crypto().sha256(public_key).to_bytes()
+        }
+    }
+}
+
+fn verify_attestation_signature(
+    env: &Env,
+    key: &IssuerKeyRecord,
+    payload: &Bytes,
+    signature: &BytesN<64>,
+) {
+    match key.algorithm {
+        KeyAlgorithm::Ed25519 => {
+            let public_key: BytesN<32> = key.public_key.clone().try_into().unwrap();
+            env.crypto().ed25519_verify(&public_key, payload, signature);
+        }
+        // Raw P-256 keys sign the sha256 of the payload; the host expects
+        // low-S signatures. Passkeys go through `submit_passkey_attestation`.
+        KeyAlgorithm::Secp256r1 => {
+            let public_key: BytesN<65> = key.public_key.clone().try_into().unwrap();
+            env.crypto()
+                .secp256r1_verify(&public_key, &env.crypto().sha256(payload), signature);
+        }
+    }
+}
+
+fn read_issuer_key(env: &Env, key_id: &BytesN<32>) -> IssuerKeyRecord {
+    match env
+        .storage()
+        .instance()
+        .get(&DataKey::IssuerKey(key_id.clone()))
+    {
+        Some(k) => k,
+        None => panic_with_error!(env, PassportError::UnknownIssuerKey),
+    }
+}
+
+// Marks `nonce` as used by `issuer`, failing if it already was. Nonces are
+// shared by all of the issuer's keys and can be used in any order; each
+// entry holds 64 of them as bits.
+fn use_nonce(env: &Env, issuer: &Address, nonce: u64) {
+    let key = DataKey::UsedNonces(issuer.clone(), nonce / 64);
+    let used: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    let bit = 1u64 << (nonce % 64);
+    if used & bit != 0 {
+        panic_with_error!(env, PassportError::InvalidNonce);
+    }
+    env.storage().persistent().set(&key, &(used | bit));
+    extend_persistent(env, &key);
+}
+
+// Applies an attestation whose signature by `key` has been checked: it must be
+// meant for this contract and `wallet`, and carry a nonce its issuer has not
+// used yet.
+fn apply_attestation(
+    env: &Env,
+    wallet: Address,
+    payload: &Bytes,
+    key: IssuerKeyRecord,
+) -> i32 {
+    let attestation = Attestation::from_xdr(env, payload).unwrap_or_else(|_| {
+        panic_with_error!(env, PassportError::InvalidAttestation);
+    });
+    if attestation.contract != env.current_contract_address() || attestation.wallet != wallet {
+        panic_with_error!(env, PassportError::InvalidAttestation);
+    }
+
+    use_nonce(env, &key.issuer, attestation.nonce);
+
+    apply_verification(
+        env,
+        key.issuer,
+        wallet,
+        attestation.vtype,
+        attestation.points,
+        attestation.expires_at,
+    )
+}
+
+// Registered issuer whose scopes include `vtype`.
+fn require_issuer_scope(env: &Env, issuer: &Address, vtype: &VerificationType) {
+    if !is_registered_issuer(env, issuer) {
+        panic_with_error!(env, PassportError::IssuerNotRegistered);
+    }
+    if !has_scope(env, issuer, vtype) {
+        panic_with_error!(env, PassportError::ScopeNotGranted);
+    }
+}
+
+#[allow(deprecated)]
+fn publish(env: &Env, name: &str, event: Event) {
+    env.events()
+        .publish((Symbol::new(env, "passport"), Symbol::new(env, name)), event);
+}
+
+fn read_ttl_config(env: &Env) -> TtlConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::TtlConfig)
+        .unwrap_or(TtlConfig {
+            threshold: DEFAULT_TTL_THRESHOLD,
+            extend_to: DEFAULT_TTL_EXTEND_TO,
+        })
+}
+
+fn extend_instance(env: &Env) {
+    let ttl = read_ttl_config(env);
+    env.storage().instance().extend_ttl(ttl.threshold, ttl.extend_to);
+}
+
+// User data lives in persistent storage, one entry per wallet, and its TTL
+// is extended every time it is touched.
+fn extend_persistent(env: &Env, key: &DataKey) {
+    let ttl = read_ttl_config(env);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, ttl.threshold, ttl.extend_to);
+}
+
 fn read_user(env: &Env, wallet: &Address) -> Option<User> {
+    require_current_schema(env);
     let key = DataKey::User(wallet.clone());
-    env.storage().instance().get(&key)
+    let user = env.storage().persistent().get(&key);
+    if user.is_some() {
+        extend_persistent(env, &key);
+    }
+    user
 }
 
 fn write_user(env: &Env, user: &User) {
+    require_current_schema(env);
     let key = DataKey::User(user.wallet.clone());
-    env.storage().instance().set(&key, user);
+    env.storage().persistent().set(&key, user);
+    extend_persistent(env, &key);
+    extend_instance(env);
+}
+
+// Each verification lives in its own entry, and writes only load and rewrite
+// that one (plus the score totals, see `read_score_totals`). The wallet's
+// types are listed in a small index, in insertion order.
+fn read_verif_types(env: &Env, wallet: &Address) -> Vec<VerificationType> {
+    require_current_schema(env);
+    let key = DataKey::VerificationTypes(wallet.clone());
+    match env.storage().persistent().get(&key) {
+        Some(types) => {
+            extend_persistent(env, &key);
+            types
+        }
+        None => Vec::new(env),
+    }
+}
+
+fn write_verif_types(env: &Env, wallet: &Address, types: &Vec<VerificationType>) {
+    require_current_schema(env);
+    let key = DataKey::VerificationTypes(wallet.clone());
+    env.storage().persistent().set(&key, types);
+    extend_persistent(env, &key);
+}
+
+fn read_verif(env: &Env, wallet: &Address, vtype: &VerificationType) -> Option<Verification> {
+    require_current_schema(env);
+    let key = DataKey::Verification(wallet.clone(), vtype.clone());
+    let verif = env.storage().persistent().get(&key);
+    if verif.is_some() {
+        extend_persistent(env, &key);
+    }
+    verif
+}
+
+fn write_verif(env: &Env, wallet: &Address, verif: &Verification) {
+    require_current_schema(env);
+    let key = DataKey::Verification(wallet.clone(), verif.vtype.clone());
+    env.storage().persistent().set(&key, verif);
+    extend_persistent(env, &key);
+}
+
+// All of the wallet's verifications, for the queries that need every entry.
+fn read_verifs(env: &Env, wallet: &Address) -> Vec<Verification> {
+    let types = read_verif_types(env, wallet);
+    // Same as `read_verif`, with the schema and TTL config read once
+    let ttl = read_ttl_config(env);
+    let storage = env.storage().persistent();
+    let mut verifs = Vec::new(env);
+    for vtype in types.iter() {
+        let key = DataKey::Verification(wallet.clone(), vtype);
+        verifs.push_back(storage.get(&key).unwrap());
+        storage.extend_ttl(&key, ttl.threshold, ttl.extend_to);
+    }
+    verifs
+}
+
+// Bumped when the point table, the half-lives or the policy's categories
+// change, which leaves every stored `ScoreTotals` stale.
+fn read_score_epoch(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ScoreEpoch)
+        .unwrap_or(0)
+}
+
+fn bump_score_epoch(env: &Env) {
+    let epoch = read_score_epoch(env).wrapping_add(1);
+    env.storage().instance().set(&DataKey::ScoreEpoch, &epoch);
+}
+
+// Adds `verif` to the totals (`sign` 1) or takes it out (`sign` -1), if it
+// counts towards the score now. Returns false, leaving the totals as they
+// were, when it does not.
+fn apply_to_totals(env: &Env, totals: &mut ScoreTotals, verif: &Verification, sign: i64) -> bool {
+    if verif.status != Status::Approved || is_expired(verif, env.ledger().timestamp()) {
+        return false;
+    }
+    if scoring::read_half_lives(env).contains_key(verif.vtype.clone()) {
+        // Their points change with time, so only the type is kept
+        match totals.decaying.first_index_of(&verif.vtype) {
+            Some(i) if sign < 0 => {
+                totals.decaying.remove(i);
+            }
+            None if sign > 0 => totals.decaying.push_back(verif.vtype.clone()),
+            _ => return false,
+        }
+    } else {
+        let points = conform_points(&read_point_table(env).get(verif.vtype.clone()), verif.points);
+        if points <= 0 {
+            return false;
+        }
+        let category = scoring::category_of(&scoring::read_policy(env), &verif.vtype);
+        let sum = totals.sums.get(category.clone()).unwrap_or(0) + sign * points as i64;
+        if sum == 0 {
+            totals.sums.remove(category);
+        } else {
+            totals.sums.set(category, sum);
+        }
+    }
+    if let (true, Some(t)) = (sign > 0, verif.expires_at) {
+        totals.next_expiry = Some(totals.next_expiry.map_or(t, |n| n.min(t)));
+    }
+    true
+}
+
+fn score_totals_of(env: &Env, verifs: &Vec<Verification>) -> ScoreTotals {
+    let mut totals = ScoreTotals {
+        epoch: read_score_epoch(env),
+        sums: Map::new(env),
+        decaying: Vec::new(env),
+        next_expiry: None,
+    };
+    for v in verifs.iter() {
+        apply_to_totals(env, &mut totals, &v, 1);
+    }
+    totals
+}
+
+// The wallet's score totals, kept in one entry so that writes and scoring
+// do not read every verification. When they are stale (or missing) they are
+// rebuilt from the verifications; the flag tells whether that happened.
+fn read_score_totals(env: &Env, wallet: &Address) -> (ScoreTotals, bool) {
+    require_current_schema(env);
+    let key = DataKey::ScoreTotals(wallet.clone());
+    if let Some(totals) = env.storage().persistent().get::<_, ScoreTotals>(&key) {
+        extend_persistent(env, &key);
+        let now = env.ledger().timestamp();
+        if totals.epoch == read_score_epoch(env) && totals.next_expiry.is_none_or(|t| now < t) {
+            return (totals, false);
+        }
+    }
+    (score_totals_of(env, &read_verifs(env, wallet)), true)
+}
+
+fn write_score_totals(env: &Env, wallet: &Address, totals: &ScoreTotals) {
+    require_current_schema(env);
+    let key = DataKey::ScoreTotals(wallet.clone());
+    env.storage().persistent().set(&key, totals);
+    extend_persistent(env, &key);
+}
+
+// For write paths: stores the totals back when they had to be rebuilt, so
+// the next write does not rebuild them again.
+fn refresh_score_totals(env: &Env, wallet: &Address) -> ScoreTotals {
+    let (totals, rebuilt) = read_score_totals(env, wallet);
+    if rebuilt {
+        write_score_totals(env, wallet, &totals);
+    }
+    totals
+}
+
+// Called after `verif` is written over `prev`: updates the totals and
+// returns the wallet's new score, `old_score` if the write did not affect it.
+fn rescore(
+    env: &Env,
+    wallet: &Address,
+    mut totals: ScoreTotals,
+    prev: Option<&Verification>,
+    verif: &Verification,
+    old_score: i32,
+) -> i32 {
+    let before = totals.clone();
+    let removed = prev.is_some_and(|prev| apply_to_totals(env, &mut totals, prev, -1));
+    if !(apply_to_totals(env, &mut totals, verif, 1) || removed) {
+        return old_score;
+    }
+    // A renewed decaying verification counts again without changing the totals
+    if totals != before {
+        write_score_totals(env, wallet, &totals);
+    }
+    effective_score(env, wallet, &totals)
+}
+
+// Points a verification adds to the score: only approved ones count.
+fn contribution(verif: &Verification) -> i32 {
+    match verif.status {
+        Status::Approved => verif.points,
+        _ => 0,
+    }
+}
+
+fn is_expired(verif: &Verification, now: u64) -> bool {
+    matches!(verif.expires_at, Some(t) if t <= now)
+}
+
+fn read_flags(env: &Env, wallet: &Address) -> Vec<Flag> {
+    require_current_schema(env);
+    let key = DataKey::Flags(wallet.clone());
+    match env.storage().persistent().get(&key) {
+        Some(flags) => {
+            extend_persistent(env, &key);
+            flags
+        }
+        None => Vec::new(env),
+    }
+}
+
+fn write_flags(env: &Env, wallet: &Address, flags: &Vec<Flag>) {
+    require_current_schema(env);
+    let key = DataKey::Flags(wallet.clone());
+    if flags.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, flags);
+        extend_persistent(env, &key);
+    }
+}
+
+fn find_flag(flags: &Vec<Flag>, issuer: &Address, reason: &Symbol) -> Option<u32> {
+    flags
+        .iter()
+        .position(|f| f.issuer == *issuer && f.reason == *reason)
+        .map(|i| i as u32)
+}
+
+fn total_penalty(env: &Env, flags: &Vec<Flag>) -> i32 {
+    let mut total = 0i32;
+    for f in flags.iter() {
+        total = safe_add_i32(total, f.penalty).unwrap_or_else(|e| {
+            panic_with_error!(env, e);
+        });
+    }
+    total
+}
+
+// Approved, non-expired verifications, conformed to the point table, decayed
+// with their type's half-life and scored under `policy` on top of `sums`,
+// minus the penalties of `flags` (the result can be negative). Every score
+// the contract returns goes through here.
+fn score_with_policy(
+    env: &Env,
+    sums: Map<Symbol, i64>,
+    verifs: &Vec<Verification>,
+    policy: &ScoringPolicy,
+    flags: &Vec<Flag>,
+) -> i32 {
+    let now = env.ledger().timestamp();
+    let table = read_point_table(env);
+    let half_lives = scoring::read_half_lives(env);
+    let mut contributions = Vec::new(env);
+    for v in verifs.iter() {
+        if v.status != Status::Approved || is_expired(&v, now) {
+            continue;
+        }
+        let mut points = conform_points(&table.get(v.vtype.clone()), v.points);
+        if let Some(half_life) = half_lives.get(v.vtype.clone()) {
+            points = scoring::decayed_points(points, v.timestamp, now, half_life);
+        }
+        if points > 0 {
+            contributions.push_back((v.vtype, points));
+        }
+    }
+    let score = scoring::compute_score(env, sums, &contributions, policy);
+    safe_sub_i32(score, total_penalty(env, flags)).unwrap_or_else(|e| {
+        panic_with_error!(env, e);
+    })
+}
+
+// Score as seen by relying parties, under the current scoring policy: the
+// totals plus the decaying verifications, read one by one. `User.score`
+// keeps the plain running total of approved points minus penalties, without
+// expiry or decay (see `get_raw_score`).
+fn effective_score(env: &Env, wallet: &Address, totals: &ScoreTotals) -> i32 {
+    let policy = scoring::read_policy(env);
+    let flags = read_flags(env, wallet);
+    // Diminishing returns rank every contribution, which the totals do not keep
+    if policy.decay_bps < scoring::BPS {
+        return score_with_policy(env, Map::new(env), &read_verifs(env, wallet), &policy, &flags);
+    }
+    let mut decaying = Vec::new(env);
+    for vtype in totals.decaying.iter() {
+        decaying.push_back(read_verif(env, wallet, &vtype).unwrap());
+    }
+    score_with_policy(env, totals.sums.clone(), &decaying, &policy, &flags)
+}
+
+fn current_score(env: &Env, wallet: &Address) -> i32 {
+    effective_score(env, wallet, &read_score_totals(env, wallet).0)
+}
+
+// Called after every write that can change the score: checkpoints the new
+// score and emits `TierChanged` when it crosses a tier threshold. Drift from
+// expiry or decay alone is only seen by `get_score` and `get_tier`.
+fn on_score_written(env: &Env, wallet: &Address, old_score: i32, new_score: i32) {
+    record_checkpoint(env, wallet, new_score);
+    let tiers = scoring::read_tiers(env);
+    let old_tier = scoring::tier_for(&tiers, old_score);
+    let new_tier = scoring::tier_for(&tiers, new_score);
+    if old_tier != new_tier {
+        publish(
+            env,
+            "TierChanged",
+            Event::TierChanged(wallet.clone(), old_tier, new_tier),
+        );
+    }
+}
+
+// Explicit expiry from the issuer, or the admin-configured default validity
+// for the type. `None` means the verification never expires.
+fn resolve_expiry(env: &Env, vtype: &VerificationType, expires_at: Option<u64>) -> Option<u64> {
+    let now = env.ledger().timestamp();
+    match expires_at {
+        Some(t) if t <= now => panic_with_error!(env, PassportError::InvalidExpiry),
+        Some(t) => Some(t),
+        None => env
+            .storage()
+            .instance()
+            .get::<_, u64>(&DataKey::DefaultValidity(vtype.clone()))
+            .map(|secs| now.saturating_add(secs)),
+    }
+}
+
+// Appends the state `verif` was left in to the wallet's audit trail. The
+// trail is a ring of the last `MAX_HISTORY_PER_USER` entries, so each append
+// overwrites the oldest one once it is full.
+fn record_history(
+    env: &Env,
+    wallet: &Address,
+    verif: &Verification,
+    action: HistoryAction,
+    actor: &Address,
+) {
+    let len_key = DataKey::HistoryLen(wallet.clone());
+    let seq: u32 = env.storage().persistent().get(&len_key).unwrap_or(0);
+    let key = DataKey::History(wallet.clone(), seq % MAX_HISTORY_PER_USER);
+    let entry = HistoryEntry {
+        seq,
+        vtype: verif.vtype.clone(),
+        action,
+        actor: actor.clone(),
+        points: verif.points,
+        status: verif.status.clone(),
+        expires_at: verif.expires_at,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.storage().persistent().set(&key, &entry);
+    extend_persistent(env, &key);
+    env.storage().persistent().set(&len_key, &(seq + 1));
+    extend_persistent(env, &len_key);
+}
+
+// Sequence numbers of the entries still in the ring.
+fn retained_history(env: &Env, wallet: &Address) -> core::ops::Range<u32> {
+    let len: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::HistoryLen(wallet.clone()))
+        .unwrap_or(0);
+    len.saturating_sub(MAX_HISTORY_PER_USER)..len
+}
+
+// Ring of the last `MAX_CHECKPOINTS_PER_USER` scores, one per ledger in which
+// the score changed.
+fn record_checkpoint(env: &Env, wallet: &Address, score: i32) {
+    let storage = env.storage().persistent();
+    let len_key = DataKey::CheckpointLen(wallet.clone());
+    let len: u32 = storage.get(&len_key).unwrap_or(0);
+    let ledger = env.ledger().sequence();
+
+    let mut seq = len;
+    if len > 0 {
+        let last: Checkpoint = storage.get(&checkpoint_key(wallet, len - 1)).unwrap();
+        if last.score == score {
+            return;
+        }
+        // Several writes in one ledger keep only the final score
+        if last.ledger == ledger {
+            seq = len - 1;
+        }
+    }
+
+    let key = checkpoint_key(wallet, seq);
+    storage.set(&key, &Checkpoint { ledger, score });
+    extend_persistent(env, &key);
+    storage.set(&len_key, &(seq + 1));
+    extend_persistent(env, &len_key);
+}
+
+// Score recorded by the wallet's last write, if any.
+fn last_checkpoint_score(env: &Env, wallet: &Address) -> Option<i32> {
+    let storage = env.storage().persistent();
+    let len: u32 = storage.get(&DataKey::CheckpointLen(wallet.clone())).unwrap_or(0);
+    if len == 0 {
+        return None;
+    }
+    storage
+        .get::<_, Checkpoint>(&checkpoint_key(wallet, len - 1))
+        .map(|c| c.score)
+}
+
+fn checkpoint_key(wallet: &Address, seq: u32) -> DataKey {
+    DataKey::Checkpoint(wallet.clone(), seq % MAX_CHECKPOINTS_PER_USER)
+}
+
+// Sequence numbers of the checkpoints still in the ring.
+fn retained_checkpoints(env: &Env, wallet: &Address) -> core::ops::Range<u32> {
+    let len: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::CheckpointLen(wallet.clone()))
+        .unwrap_or(0);
+    len.saturating_sub(MAX_CHECKPOINTS_PER_USER)..len
+}
+
+// Distinct issuers that must confirm a verification of `vtype` before it
+// counts. 1 means a single issuer and the usual review.
+fn read_quorum(env: &Env, vtype: &VerificationType) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Quorum(vtype.clone()))
+        .unwrap_or(1)
+}
+
+// Counts `verif.issuer` among the issuers that confirmed `verif`. Under a
+// quorum, the confirmation that reaches it approves the verification; returns
+// whether this one did. An issuer that already confirmed adds nothing, so
+// after a reset to pending the same attesters cannot re-approve it.
+fn add_confirmation(env: &Env, verif: &mut Verification) -> bool {
+    if verif.attesters.contains(&verif.issuer) {
+        return false;
+    }
+    verif.attesters.push_back(verif.issuer.clone());
+    let quorum = read_quorum(env, &verif.vtype);
+    let reached =
+        quorum > 1 && verif.status == Status::Pending && verif.attesters.len() >= quorum;
+    if reached {
+        verif.status = Status::Approved;
+    }
+    reached
+}
+
+// Allowed status moves. A rejected verification has to go back to
+// `Pending` (reset or re-submission) before it can be approved.
+fn is_valid_transition(from: &Status, to: &Status) -> bool {
+    matches!(
+        (from, to),
+        (Status::Pending, Status::Approved)
+            | (Status::Pending, Status::Rejected)
+            | (Status::Approved, Status::Rejected)
+            | (Status::Approved, Status::Pending)
+            | (Status::Rejected, Status::Pending)
+    )
+}
+
+fn set_status(
+    env: &Env,
+    reviewer: &Address,
+    wallet: &Address,
+    vtype: &VerificationType,
+    to: Status,
+) -> i32 {
+    require_not_paused(env, PauseSwitch::VerificationWrites);
+    reviewer.require_auth();
+    require_issuer_scope(env, reviewer, vtype);
+    let mut user = match read_user(env, wallet) {
+        Some(u) => u,
+        None => panic_with_error!(env, PassportError::NotRegistered),
+    };
+
+    let mut verif = match read_verif(env, wallet, vtype) {
+        Some(v) => v,
+        None => panic_with_error!(env, PassportError::VerificationNotFound),
+    };
+    let from = verif.status.clone();
+    if !is_valid_transition(&from, &to) {
+        panic_with_error!(env, PassportError::InvalidStatusTransition);
+    }
+    if to == Status::Approved && verif.attesters.len() < read_quorum(env, vtype) {
+        panic_with_error!(env, PassportError::QuorumNotReached);
+    }
+    let totals = refresh_score_totals(env, wallet);
+    let old_score = effective_score(env, wallet, &totals);
+    let prev = verif.clone();
+
+    let old_contribution = contribution(&verif);
+    verif.status = to.clone();
+    let score_minus_old = safe_sub_i32(user.score, old_contribution).unwrap_or_else(|e| {
+        panic_with_error!(env, e);
+    });
+    let new_score = safe_add_i32(score_minus_old, contribution(&verif)).unwrap_or_else(|e| {
+        panic_with_error!(env, e);
+    });
+
+    write_verif(env, wallet, &verif);
+    record_history(env, wallet, &verif, HistoryAction::StatusChanged, reviewer);
+    user.score = new_score;
+    write_user(env, &user);
+
+    publish(
+        env,
+        "VerificationStatusChanged",
+        Event::VerificationStatusChanged(wallet.clone(), vtype.clone(), from, to),
+    );
+
+    let effective = rescore(env, wallet, totals, Some(&prev), &verif, old_score);
+    on_score_written(env, wallet, old_score, effective);
+    effective
 }
 
-fn read_verifs(env: &Env, wallet: &Address) -> Vec<Verification> {
-    let key = DataKey::Verifications(wallet.clone());
-    env.storage().instance().get(&key).unwrap_or_else(|| Vec::new(&env))
-}
+// Writes a verification on behalf of `issuer`, whose authorization has
+// already been checked by the caller (signature on the transaction or on an
+// off-chain attestation).
+fn apply_verification(
+    env: &Env,
+    issuer: Address,
+    wallet: Address,
+    vtype: VerificationType,
+    points: i32,
+    expires_at: Option<u64>,
+) -> i32 {
+    require_not_paused(env, PauseSwitch::VerificationWrites);
+    require_issuer_scope(env, &issuer, &vtype);
+    if !points_allowed(&read_point_table(env).get(vtype.clone()), points) {
+        panic_with_error!(env, PassportError::InvalidPoints);
+    }
+    let expires_at = resolve_expiry(env, &vtype, expires_at);
+
+    let mut user = match read_user(env, &wallet) {
+        Some(u) => u,
+        None => panic_with_error!(env, PassportError::NotRegistered),
+    };
+
+    let prev = read_verif(env, &wallet, &vtype);
+    if prev.is_none() && user.ver_count >= MAX_VERIFICATIONS_PER_USER {
+        panic_with_error!(env, PassportError::TooManyVerifications);
+    }
+    let totals = refresh_score_totals(env, &wallet);
+    let old_score = effective_score(env, &wallet, &totals);
+    let now = env.ledger().timestamp();
+
+    let old_points = prev.as_ref().map_or(0, |v| v.points);
+    let old_contribution = prev.as_ref().map_or(0, contribution);
+    let prev_status = prev.as_ref().map(|v| v.status.clone());
+
+    // Re-submitting a rejected or revoked verification sends it back to
+    // review, with only the new issuer's confirmation
+    let created = matches!(
+        prev_status,
+        Some(Status::Rejected) | Some(Status::Revoked) | None
+    );
+    let (status, attesters) = match &prev {
+        Some(v) if !created => (v.status.clone(), v.attesters.clone()),
+        _ => (Status::Pending, Vec::new(env)),
+    };
+
+    let mut new_verif = Verification {
+        vtype: vtype.clone(),
+        points,
+        timestamp: now,
+        expires_at,
+        issuer,
+        status,
+        attesters,
+    };
+    // Under a quorum, the confirmations themselves approve the verification
+    let quorum_reached = add_confirmation(env, &mut new_verif);
+
+    // Only approved verifications count towards the score
+    let score_minus_old = safe_sub_i32(user.score, old_contribution).unwrap_or_else(|e| {
+        panic_with_error!(env, e);
+    });
+    let new_score = safe_add_i32(score_minus_old, contribution(&new_verif)).unwrap_or_else(|e| {
+        panic_with_error!(env, e);
+    });
+
+    write_verif(env, &wallet, &new_verif);
+    // The confirmation that completes a quorum is logged as the approval
+    let action = if quorum_reached {
+        HistoryAction::StatusChanged
+    } else if created {
+        HistoryAction::Created
+    } else {
+        HistoryAction::Updated
+    };
+    record_history(env, &wallet, &new_verif, action, &new_verif.issuer);
+    if prev_status.is_none() {
+        let mut types = read_verif_types(env, &wallet);
+        types.push_back(vtype.clone());
+        write_verif_types(env, &wallet, &types);
+        user.ver_count += 1;
+    }
+
+    user.score = new_score;
+    write_user(env, &user);
+    if prev_status == Some(Status::Revoked) {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Revocation(wallet.clone(), vtype.clone()));
+    }
+
+    let effective = rescore(env, &wallet, totals, prev.as_ref(), &new_verif, old_score);
+    publish(
+        env,
+        "VerificationUpserted",
+        Event::VerificationUpserted(wallet.clone(), vtype.clone(), old_points, points, effective),
+    );
+    if quorum_reached {
+        publish(
+            env,
+            "VerificationStatusChanged",
+            Event::VerificationStatusChanged(
+                wallet.clone(),
+                vtype,
+                Status::Pending,
+                Status::Approved,
+            ),
+        );
+    }
+    on_score_written(env, &wallet, old_score, effective);
+
+    effective
+}
+
+fn safe_add_i32(a: i32, b: i32) -> Result<i32, PassportError> {
+    a.checked_add(b).ok_or(PassportError::Overflow)
+}
+
+fn safe_sub_i32(a: i32, b: i32) -> Result<i32, PassportError> {
+    a.checked_sub(b).ok_or(PassportError::Overflow)
+}
+
+#[contract]
+pub struct StellarPassport;
+
+#[contractimpl]
+impl StellarPassport {
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        migration::write_schema_version(&env, SCHEMA_VERSION);
+
+        publish(&env, "Initialized", Event::Initialized(admin));
+    }
+
+    pub fn version(_env: Env) -> u32 {
+        CONTRACT_VERSION
+    }
+
+    pub fn schema_version(env: Env) -> u32 {
+        migration::read_schema_version(&env)
+    }
+
+    pub fn register(env: Env, wallet: Address, name: String, surnames: String) {
+        require_not_paused(&env, PauseSwitch::Registration);
+        wallet.require_auth();
+        // A v1 passport not indexed yet is still registered
+        if read_user(&env, &wallet).is_some() || migration::has_legacy_passport(&env, &wallet) {
+            panic_with_error!(&env, PassportError::AlreadyRegistered);
+        }
+
+        let user = User {
+            wallet: wallet.clone(),
+            name,
+            surnames,
+            score: 0,
+            ver_count: 0,
+        };
+        write_user(&env, &user);
+        migration::index_wallet(&env, &wallet);
+        record_checkpoint(&env, &wallet, 0);
+
+        publish(&env, "UserRegistered", Event::UserRegistered(wallet));
+    }
+
+    // ===== Admin =====
+
+    pub fn get_admin(env: Env) -> Address {
+        read_admin(&env)
+    }
+
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PendingAdmin)
+    }
+
+    // First step of the transfer: the current admin nominates a successor.
+    // Proposing again replaces the previous nominee.
+    pub fn propose_admin(env: Env, new_admin: Address) {
+        let admin = require_admin(&env);
+        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
+
+        publish(&env, "AdminProposed", Event::AdminProposed(admin, new_admin));
+    }
+
+    // Second step: the nominee signs to take over.
+    pub fn accept_admin(env: Env) {
+        let new_admin: Address = match env.storage().instance().get(&DataKey::PendingAdmin) {
+            Some(a) => a,
+            None => panic_with_error!(&env, PassportError::NoPendingAdmin),
+        };
+        new_admin.require_auth();
+
+        let old_admin = read_admin(&env);
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        publish(&env, "AdminChanged", Event::AdminChanged(old_admin, new_admin));
+    }
+
+    // ===== Emergency pause (admin) =====
+
+    // Stops every guarded write at once, e.g. when an issuer key leaks.
+    // Reads, admin functions and revocations keep working.
+    pub fn pause(env: Env) {
+        require_admin(&env);
+        env.storage().instance().set(&DataKey::Paused, &true);
+
+        publish(&env, "Paused", Event::Paused);
+    }
+
+    pub fn unpause(env: Env) {
+        require_admin(&env);
+        env.storage().instance().remove(&DataKey::Paused);
+
+        publish(&env, "Unpaused", Event::Unpaused);
+    }
+
+    // Finer-grained circuit breaker for one group of functions.
+    pub fn set_paused(env: Env, switch: PauseSwitch, paused: bool) {
+        require_admin(&env);
+        let key = DataKey::PauseSwitch(switch.clone());
+        if paused {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+
+        publish(&env, "PauseSwitchSet", Event::PauseSwitchSet(switch, paused));
+    }
+
+    // Whether writes in `switch` are currently stopped, globally or by
+    // their own switch.
+    pub fn is_paused(env: Env, switch: PauseSwitch) -> bool {
+        is_paused(&env, &switch)
+    }
+
+    // ===== Storage config (admin) =====
+
+    pub fn get_ttl_config(env: Env) -> TtlConfig {
+        read_ttl_config(&env)
+    }
+
+    pub fn set_ttl_config(env: Env, threshold: u32, extend_to: u32) {
+        require_admin(&env);
+        if threshold >= extend_to || extend_to > env.storage().max_ttl() {
+            panic_with_error!(&env, PassportError::InvalidConfig);
+        }
+
+        env.storage().instance().set(
+            &DataKey::TtlConfig,
+            &TtlConfig {
+                threshold,
+                extend_to,
+            },
+        );
+        extend_instance(&env);
+    }
+
+    // ===== Upgrade & migration (admin) =====
+
+    // Replaces the contract code. Deliberately skips the schema check so an
+    // admin can always move forward from a build that is too old for the
+    // stored data. Run `migrate` afterwards if the new build bumps the schema.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        read_admin(&env).require_auth();
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        publish(&env, "Upgraded", Event::Upgraded(new_wasm_hash));
+    }
+
+    // Adds wallets registered under the v1 layout, before the wallet index
+    // existed, so that `migrate` can reach them. Wallets found after the
+    // migration has finished are migrated on the spot. Other wallets are
+    // skipped.
+    pub fn index_wallets(env: Env, wallets: Vec<Address>) {
+        require_admin(&env);
+        for wallet in wallets.iter() {
+            if migration::has_legacy_passport(&env, &wallet) {
+                migration::index_legacy_wallet(&env, &wallet);
+            }
+        }
+    }
+
+    // Resumable: each call migrates up to `batch_size` wallets and returns
+    // how many are left. `schema_version()` is bumped when the last batch is done.
+    pub fn migrate(env: Env, batch_size: u32) -> u32 {
+        require_admin(&env);
+        migration::migrate_batch(&env, batch_size)
+    }
+
+    // ===== Verification validity (admin) =====
+
+    // Validity applied when an issuer does not set an explicit expiry.
+    // `0` removes the default, so the type never expires.
+    pub fn set_default_validity(env: Env, vtype: VerificationType, secs: u64) {
+        require_admin(&env);
+        let key = DataKey::DefaultValidity(vtype);
+        if secs == 0 {
+            env.storage().instance().remove(&key);
+        } else {
+            env.storage().instance().set(&key, &secs);
+        }
+    }
+
+    pub fn get_default_validity(env: Env, vtype: VerificationType) -> Option<u64> {
+        env.storage()
+            .instance()
+            .get(&DataKey::DefaultValidity(vtype))
+    }
+
+    // ===== Issuer quorum (admin) =====
+
+    // Distinct issuers that must confirm a verification of `vtype` before it
+    // is approved and its points count. `1` (the default) removes the quorum.
+    // Verifications already approved are not touched.
+    pub fn set_quorum(env: Env, vtype: VerificationType, threshold: u32) {
+        require_admin(&env);
+        if threshold == 0 || threshold > MAX_QUORUM {
+            panic_with_error!(&env, PassportError::InvalidConfig);
+        }
+        let key = DataKey::Quorum(vtype.clone());
+        if threshold == 1 {
+            env.storage().instance().remove(&key);
+        } else {
+            env.storage().instance().set(&key, &threshold);
+        }
+
+        publish(&env, "QuorumSet", Event::QuorumSet(vtype, threshold));
+    }
+
+    pub fn get_quorum(env: Env, vtype: VerificationType) -> u32 {
+        read_quorum(&env, &vtype)
+    }
+
+    // ===== Point table (admin) =====
+
+    // Makes scores comparable across issuers. Existing verifications keep
+    // their points, but scores count them as the rule allows; see
+    // `recompute_score`.
+    pub fn set_point_rule(env: Env, vtype: VerificationType, rule: PointRule) {
+        require_admin(&env);
+        let valid = match rule {
+            PointRule::Fixed(p) => p > 0,
+            PointRule::Range(min, max) => min > 0 && min <= max,
+        };
+        if !valid {
+            panic_with_error!(&env, PassportError::InvalidConfig);
+        }
+
+        let mut table = read_point_table(&env);
+        table.set(vtype.clone(), rule.clone());
+        env.storage().instance().set(&DataKey::PointTable, &table);
+        bump_score_epoch(&env);
+
+        publish(&env, "PointRuleSet", Event::PointRuleSet(vtype, rule));
+    }
+
+    pub fn remove_point_rule(env: Env, vtype: VerificationType) {
+        require_admin(&env);
+        let mut table = read_point_table(&env);
+        if table.remove(vtype.clone()).is_none() {
+            return;
+        }
+        if table.is_empty() {
+            env.storage().instance().remove(&DataKey::PointTable);
+        } else {
+            env.storage().instance().set(&DataKey::PointTable, &table);
+        }
+        bump_score_epoch(&env);
+
+        publish(&env, "PointRuleRemoved", Event::PointRuleRemoved(vtype));
+    }
+
+    pub fn get_point_table(env: Env) -> Map<VerificationType, PointRule> {
+        read_point_table(&env)
+    }
+
+    // ===== Scoring policy (admin) =====
+
+    pub fn set_scoring_policy(env: Env, policy: ScoringPolicy) {
+        require_admin(&env);
+        scoring::validate_policy(&env, &policy);
+        if policy.categories != scoring::read_policy(&env).categories {
+            bump_score_epoch(&env);
+        }
+        env.storage().instance().set(&DataKey::ScoringPolicy, &policy);
+
+        publish(&env, "ScoringPolicySet", Event::ScoringPolicySet(policy));
+    }
+
+    pub fn get_scoring_policy(env: Env) -> ScoringPolicy {
+        scoring::read_policy(&env)
+    }
+
+    // Verifications of `vtype` lose half their points every `secs`, counted
+    // from their timestamp (renewals restart it). `0` disables decay.
+    pub fn set_half_life(env: Env, vtype: VerificationType, secs: u64) {
+        require_admin(&env);
+        let mut half_lives = scoring::read_half_lives(&env);
+        if secs == 0 {
+            half_lives.remove(vtype.clone());
+        } else {
+            half_lives.set(vtype.clone(), secs);
+        }
+        if half_lives.is_empty() {
+            env.storage().instance().remove(&DataKey::HalfLives);
+        } else {
+            env.storage().instance().set(&DataKey::HalfLives, &half_lives);
+        }
+        bump_score_epoch(&env);
+
+        publish(&env, "HalfLifeSet", Event::HalfLifeSet(vtype, secs));
+    }
+
+    pub fn get_half_lives(env: Env) -> Map<VerificationType, u64> {
+        scoring::read_half_lives(&env)
+    }
+
+    // Thresholds must be strictly increasing. An empty list removes tiers.
+    pub fn set_tiers(env: Env, tiers: Vec<Tier>) {
+        require_admin(&env);
+        scoring::validate_tiers(&env, &tiers);
+        if tiers.is_empty() {
+            env.storage().instance().remove(&DataKey::Tiers);
+        } else {
+            env.storage().instance().set(&DataKey::Tiers, &tiers);
+        }
+
+        publish(&env, "TiersSet", Event::TiersSet(tiers));
+    }
+
+    pub fn get_tiers(env: Env) -> Vec<Tier> {
+        scoring::read_tiers(&env)
+    }
+
+    // Score the wallet would have under `policy`, to evaluate a change
+    // before switching to it.
+    pub fn preview_score(env: Env, wallet: Address, policy: ScoringPolicy) -> i32 {
+        scoring::validate_policy(&env, &policy);
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+        let verifs = read_verifs(&env, &wallet);
+        score_with_policy(&env, Map::new(&env), &verifs, &policy, &read_flags(&env, &wallet))
+    }
+
+    // ===== Issuer registry (admin) =====
+
+    pub fn add_issuer(env: Env, issuer: Address) {
+        require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::Issuer(issuer.clone()), &true);
+
+        publish(&env, "IssuerAdded", Event::IssuerAdded(issuer));
+    }
+
+    pub fn remove_issuer(env: Env, issuer: Address) {
+        require_admin(&env);
+        if !is_registered_issuer(&env, &issuer) {
+            panic_with_error!(&env, PassportError::IssuerNotRegistered);
+        }
+        env.storage()
+            .instance()
+            .remove(&DataKey::Issuer(issuer.clone()));
+
+        // Drop every scope so a re-added issuer starts from scratch
+        for vtype in read_scopes(&env, &issuer).iter() {
+            env.storage()
+                .instance()
+                .remove(&DataKey::IssuerScope(issuer.clone(), vtype));
+        }
+        write_scopes(&env, &issuer, &Vec::new(&env));
+
+        // Same for signing keys. The issuer's used nonces are kept so old
+        // attestations cannot be replayed if it is registered again.
+        for key in read_issuer_keys(&env, &issuer).iter() {
+            env.storage().instance().remove(&DataKey::IssuerKey(key));
+        }
+        write_issuer_keys(&env, &issuer, &Vec::new(&env));
+
+        publish(&env, "IssuerRemoved", Event::IssuerRemoved(issuer));
+    }
+
+    pub fn is_issuer(env: Env, issuer: Address) -> bool {
+        is_registered_issuer(&env, &issuer)
+    }
+
+    pub fn grant_scope(env: Env, issuer: Address, vtype: VerificationType) {
+        require_admin(&env);
+        if !is_registered_issuer(&env, &issuer) {
+            panic_with_error!(&env, PassportError::IssuerNotRegistered);
+        }
+        if has_scope(&env, &issuer, &vtype) {
+            return;
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::IssuerScope(issuer.clone(), vtype.clone()), &true);
+        let mut scopes = read_scopes(&env, &issuer);
+        scopes.push_back(vtype.clone());
+        write_scopes(&env, &issuer, &scopes);
+
+        publish(&env, "ScopeGranted", Event::ScopeGranted(issuer, vtype));
+    }
+
+    pub fn revoke_scope(env: Env, issuer: Address, vtype: VerificationType) {
+        require_admin(&env);
+        if !has_scope(&env, &issuer, &vtype) {
+            panic_with_error!(&env, PassportError::ScopeNotGranted);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&DataKey::IssuerScope(issuer.clone(), vtype.clone()));
+        let mut scopes = read_scopes(&env, &issuer);
+        if let Some(idx) = scopes.first_index_of(&vtype) {
+            scopes.remove(idx);
+        }
+        write_scopes(&env, &issuer, &scopes);
+
+        publish(&env, "ScopeRevoked", Event::ScopeRevoked(issuer, vtype));
+    }
+
+    pub fn issuer_scopes(env: Env, issuer: Address) -> Vec<VerificationType> {
+        read_scopes(&env, &issuer)
+    }
+
+    // Registers a public key whose attestations are applied on behalf of
+    // `issuer`, under the issuer's scopes. Returns the key's identifier.
+    pub fn add_issuer_key(
+        env: Env,
+        issuer: Address,
+        algorithm: KeyAlgorithm,
+        public_key: Bytes,
+    ) -> BytesN<32> {
+        require_admin(&env);
+        if !is_registered_issuer(&env, &issuer) {
+            panic_with_error!(&env, PassportError::IssuerNotRegistered);
+        }
+        let key_id = issuer_key_id(&env, &algorithm, &public_key);
+        let key_entry = DataKey::IssuerKey(key_id.clone());
+        match env.storage().instance().get::<_, IssuerKeyRecord>(&key_entry) {
+            Some(key) if key.issuer == issuer && key.algorithm == algorithm => return key_id,
+            Some(_) => panic_with_error!(&env, PassportError::KeyAlreadyRegistered),
+            None => {}
+        }
+
+        env.storage().instance().set(
+            &key_entry,
+            &IssuerKeyRecord {
+                issuer: issuer.clone(),
+                algorithm,
+                public_key,
+            },
+        );
+        let mut keys = read_issuer_keys(&env, &issuer);
+        keys.push_back(key_id.clone());
+        write_issuer_keys(&env, &issuer, &keys);
 
-fn write_verifs(env: &Env, wallet: &Address, verifs: &Vec<Verification>) {
-    let key = DataKey::Verifications(wallet.clone());
-    env.storage().instance().set(&key, verifs);
-}
+        publish(&env, "IssuerKeyAdded", Event::IssuerKeyAdded(issuer, key_id.clone()));
+        key_id
+    }
 
-fn safe_add_i32(a: i32, b: i32) -> Result<i32, PassportError> {
-    a.checked_add(b).ok_or(PassportError::Overflow)
-}
+    pub fn remove_issuer_key(env: Env, key_id: BytesN<32>) {
+        require_admin(&env);
+        let key_entry = DataKey::IssuerKey(key_id.clone());
+        let issuer = match env.storage().instance().get::<_, IssuerKeyRecord>(&key_entry) {
+            Some(key) => key.issuer,
+            None => panic_with_error!(&env, PassportError::UnknownIssuerKey),
+        };
 
-fn safe_sub_i32(a: i32, b: i32) -> Result<i32, PassportError> {
-    a.checked_sub(b).ok_or(PassportError::Overflow)
-}
+        env.storage().instance().remove(&key_entry);
+        let mut keys = read_issuer_keys(&env, &issuer);
+        if let Some(idx) = keys.first_index_of(&key_id) {
+            keys.remove(idx);
+        }
+        write_issuer_keys(&env, &issuer, &keys);
 
-#[contract]
-pub struct StellarPassport;
+        publish(&env, "IssuerKeyRemoved", Event::IssuerKeyRemoved(issuer, key_id));
+    }
 
-#[contractimpl]
-impl StellarPassport {
-    pub fn version(_env: Env) -> u32 {
-        1
+    pub fn get_issuer_key(env: Env, key_id: BytesN<32>) -> Option<IssuerKeyRecord> {
+        env.storage().instance().get(&DataKey::IssuerKey(key_id))
     }
 
-    pub fn register(env: Env, wallet: Address, name: String, surnames: String) {
-        wallet.require_auth();
-        if read_user(&env, &wallet).is_some() {
-            panic_with_error!(&env, PassportError::AlreadyRegistered);
+    pub fn issuer_keys(env: Env, issuer: Address) -> Vec<BytesN<32>> {
+        read_issuer_keys(&env, &issuer)
+    }
+
+    // Anyone can keep a passport alive by paying for its TTL extension.
+    pub fn bump_passport(env: Env, wallet: Address) {
+        require_current_schema(&env);
+        let user_key = DataKey::User(wallet.clone());
+        if !env.storage().persistent().has(&user_key) {
+            panic_with_error!(&env, PassportError::NotRegistered);
         }
+        extend_persistent(&env, &user_key);
 
-        let user = User {
-            wallet: wallet.clone(),
-            name,
-            surnames,
-            score: 0,
-            ver_count: 0,
-        };
-        write_user(&env, &user);
+        // Reading the index extends it
+        for vtype in read_verif_types(&env, &wallet).iter() {
+            extend_persistent(&env, &DataKey::Verification(wallet.clone(), vtype));
+        }
+        let totals_key = DataKey::ScoreTotals(wallet.clone());
+        if env.storage().persistent().has(&totals_key) {
+            extend_persistent(&env, &totals_key);
+        }
+        let history = retained_history(&env, &wallet);
+        if !history.is_empty() {
+            extend_persistent(&env, &DataKey::HistoryLen(wallet.clone()));
+        }
+        for seq in history {
+            let slot = seq % MAX_HISTORY_PER_USER;
+            extend_persistent(&env, &DataKey::History(wallet.clone(), slot));
+        }
+        let checkpoints = retained_checkpoints(&env, &wallet);
+        if !checkpoints.is_empty() {
+            extend_persistent(&env, &DataKey::CheckpointLen(wallet.clone()));
+        }
+        for seq in checkpoints {
+            extend_persistent(&env, &checkpoint_key(&wallet, seq));
+        }
+        let flags_key = DataKey::Flags(wallet);
+        if env.storage().persistent().has(&flags_key) {
+            extend_persistent(&env, &flags_key);
+        }
+        extend_instance(&env);
+    }
+
+    // Records the score a change to the point table left the wallet with:
+    // rebuilds its score totals, checkpoints the score and emits
+    // `TierChanged` if it crossed a threshold. Stored points are not
+    // rewritten, since scores already count them through the table, so
+    // anyone can trigger it.
+    pub fn recompute_score(env: Env, wallet: Address) -> i32 {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
 
-        env.events().publish(
-            (Symbol::new(&env, "passport"), Symbol::new(&env, "UserRegistered")),
-            Event::UserRegistered(wallet),
+        let totals = score_totals_of(&env, &read_verifs(&env, &wallet));
+        write_score_totals(&env, &wallet, &totals);
+        let new_score = effective_score(&env, &wallet, &totals);
+        let old_score = last_checkpoint_score(&env, &wallet).unwrap_or(new_score);
+        publish(
+            &env,
+            "ScoreRecomputed",
+            Event::ScoreRecomputed(wallet.clone(), old_score, new_score),
         );
+        on_score_written(&env, &wallet, old_score, new_score);
+        new_score
     }
 
-    pub fn get_score(env: Env, wallet: Address) -> i32 {
+    // Stored running total of approved points, before expiry, decay, the
+    // point table and the scoring policy.
+    pub fn get_raw_score(env: Env, wallet: Address) -> i32 {
         match read_user(&env, &wallet) {
-            Some(u) => u.score,
+            Some(user) => user.score,
             None => panic_with_error!(&env, PassportError::NotRegistered),
         }
     }
 
+    // Evaluates a requirement tree in-contract, so consuming contracts do not
+    // have to pull and loop over `get_verifications`.
+    pub fn meets_requirements(env: Env, wallet: Address, req: Requirement) -> bool {
+        requirements::validate(&env, &req);
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+        let verifs = read_verifs(&env, &wallet);
+        requirements::evaluate(&env, &req, &wallet, &verifs, &mut None)
+    }
+
+    // Highest tier reached by the current score, `none` below every threshold.
+    pub fn get_tier(env: Env, wallet: Address) -> Symbol {
+        let score = Self::get_score(env.clone(), wallet);
+        scoring::tier_for(&scoring::read_tiers(&env), score)
+    }
+
+    pub fn get_score(env: Env, wallet: Address) -> i32 {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+        current_score(&env, &wallet)
+    }
+
+    // Score as of ledger `ledger`, from the checkpoints written whenever the
+    // score changed, so snapshots cannot be gamed by writes made after them.
+    // Drift from expiry or decay between writes is not reflected.
+    pub fn get_score_at(env: Env, wallet: Address, ledger: u32) -> i32 {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+
+        let read = |seq: u32| -> Checkpoint {
+            env.storage()
+                .persistent()
+                .get(&checkpoint_key(&wallet, seq))
+                .unwrap()
+        };
+        // Nothing is known before the oldest checkpoint still retained
+        let retained = retained_checkpoints(&env, &wallet);
+        if retained.is_empty() || ledger < read(retained.start).ledger {
+            panic_with_error!(&env, PassportError::CheckpointUnavailable);
+        }
+
+        // Last checkpoint at or before `ledger`: it is always in [lo, hi)
+        let (mut lo, mut hi) = (retained.start, retained.end);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if read(mid).ledger <= ledger {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        read(lo).score
+    }
+
+    // Points still under review, so frontends can show the score after review.
+    pub fn get_pending_points(env: Env, wallet: Address) -> i32 {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+
+        let now = env.ledger().timestamp();
+        let table = read_point_table(&env);
+        let mut pending = 0i32;
+        for v in read_verifs(&env, &wallet).iter() {
+            if v.status == Status::Pending && !is_expired(&v, now) {
+                let points = conform_points(&table.get(v.vtype.clone()), v.points);
+                pending = safe_add_i32(pending, points).unwrap_or_else(|e| {
+                    panic_with_error!(&env, e);
+                });
+            }
+        }
+        pending
+    }
+
     pub fn get_verifications(env: Env, wallet: Address) -> Vec<Verification> {
         if read_user(&env, &wallet).is_none() {
             panic_with_error!(&env, PassportError::NotRegistered);
@@ -86,78 +1509,451 @@ impl StellarPassport {
         read_verifs(&env, &wallet)
     }
 
+    // Direct lookup of one verification, without loading the whole list
+    // into the caller.
+    pub fn get_verification(
+        env: Env,
+        wallet: Address,
+        vtype: VerificationType,
+    ) -> Option<Verification> {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+        read_verif(&env, &wallet, &vtype)
+    }
+
+    // Up to `limit` verifications from position `start`, counted among those
+    // with `status` when a filter is given. Pages past the end are empty.
+    pub fn get_verifications_page(
+        env: Env,
+        wallet: Address,
+        start: u32,
+        limit: u32,
+        status: Option<Status>,
+    ) -> Vec<Verification> {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+
+        let mut page = Vec::new(&env);
+        let types = read_verif_types(&env, &wallet).into_iter();
+        match status {
+            // Only the entries on the page are loaded
+            None => {
+                for vtype in types.skip(start as usize).take(limit as usize) {
+                    page.push_back(read_verif(&env, &wallet, &vtype).unwrap());
+                }
+            }
+            Some(status) => {
+                let matching = types
+                    .map(|vtype| read_verif(&env, &wallet, &vtype).unwrap())
+                    .filter(|v| v.status == status);
+                for v in matching.skip(start as usize).take(limit as usize) {
+                    page.push_back(v);
+                }
+            }
+        }
+        page
+    }
+
+    // Audit trail of the wallet's verifications, oldest first, from sequence
+    // number `start`. Only the last `MAX_HISTORY_PER_USER` entries are kept;
+    // earlier sequence numbers start at the oldest one retained.
+    pub fn get_history(env: Env, wallet: Address, start: u32, limit: u32) -> Vec<HistoryEntry> {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+
+        let retained = retained_history(&env, &wallet);
+        let from = start.max(retained.start);
+        let to = retained.end.min(from.saturating_add(limit));
+        let mut entries = Vec::new(&env);
+        for seq in from..to {
+            let key = DataKey::History(wallet.clone(), seq % MAX_HISTORY_PER_USER);
+            entries.push_back(env.storage().persistent().get(&key).unwrap());
+        }
+        entries
+    }
+
+    // Verifications expiring within `within_secs` from now, including the
+    // ones already expired, so backends can prompt re-verification.
+    pub fn get_expiring(env: Env, wallet: Address, within_secs: u64) -> Vec<Verification> {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+
+        let deadline = env.ledger().timestamp().saturating_add(within_secs);
+        let mut expiring = Vec::new(&env);
+        for v in read_verifs(&env, &wallet).iter() {
+            if matches!(v.expires_at, Some(t) if t <= deadline) {
+                expiring.push_back(v);
+            }
+        }
+        expiring
+    }
+
     pub fn upsert_verification(
         env: Env,
+        issuer: Address,
         wallet: Address,
         vtype: VerificationType,
         points: i32,
+        expires_at: Option<u64>,
     ) -> i32 {
-        wallet.require_auth();
-        if points <= 0 {
-            panic_with_error!(&env, PassportError::InvalidPoints);
+        issuer.require_auth();
+        apply_verification(&env, issuer, wallet, vtype, points, expires_at)
+    }
+
+    // Same as `upsert_verification`, for issuers that sign attestations
+    // off-chain instead of submitting transactions. The payload is the XDR of
+    // an `Attestation`, and its nonce must not have been used by the issuer.
+    pub fn submit_attestation(
+        env: Env,
+        wallet: Address,
+        payload: Bytes,
+        signature: BytesN<64>,
+        issuer_key: BytesN<32>,
+    ) -> i32 {
+        let key = read_issuer_key(&env, &issuer_key);
+        verify_attestation_signature(&env, &key, &payload, &signature);
+        apply_attestation(&env, wallet, &payload, key)
+    }
+
+    // Same as `submit_attestation`, for a secp256r1 issuer key held by a
+    // passkey: `signature` is a WebAuthn assertion whose challenge is the
+    // sha256 of the payload.
+    pub fn submit_passkey_attestation(
+        env: Env,
+        wallet: Address,
+        payload: Bytes,
+        authenticator_data: Bytes,
+        client_data_json: Bytes,
+        signature: BytesN<64>,
+        issuer_key: BytesN<32>,
+    ) -> i32 {
+        let key = read_issuer_key(&env, &issuer_key);
+        if key.algorithm != KeyAlgorithm::Secp256r1 {
+            panic_with_error!(&env, PassportError::InvalidAttestation);
         }
+        let public_key: BytesN<65> = key.public_key.clone().try_into().unwrap();
+        webauthn::verify_assertion(
+            &env,
+            &public_key,
+            &payload,
+            &authenticator_data,
+            &client_data_json,
+            &signature,
+        );
+        apply_attestation(&env, wallet, &payload, key)
+    }
 
+    // Whether an attestation by one of `issuer`'s keys already used `nonce`.
+    pub fn is_nonce_used(env: Env, issuer: Address, nonce: u64) -> bool {
+        let used: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::UsedNonces(issuer, nonce / 64))
+            .unwrap_or(0);
+        used & (1u64 << (nonce % 64)) != 0
+    }
+
+    // Re-verification: the issuer confirms the verification again, which
+    // refreshes its timestamp and sets a new expiry. Like an upsert, it counts
+    // towards the type's quorum.
+    pub fn renew_verification(
+        env: Env,
+        issuer: Address,
+        wallet: Address,
+        vtype: VerificationType,
+        expires_at: Option<u64>,
+    ) -> i32 {
+        require_not_paused(&env, PauseSwitch::VerificationWrites);
+        issuer.require_auth();
+        require_issuer_scope(&env, &issuer, &vtype);
         let mut user = match read_user(&env, &wallet) {
             Some(u) => u,
             None => panic_with_error!(&env, PassportError::NotRegistered),
         };
+        let expires_at = resolve_expiry(&env, &vtype, expires_at);
 
-        let mut verifs = read_verifs(&env, &wallet);
-        let now = env.ledger().timestamp();
+        let mut verif = match read_verif(&env, &wallet, &vtype) {
+            Some(v) => v,
+            None => panic_with_error!(&env, PassportError::VerificationNotFound),
+        };
+        // A revoked verification has to be re-issued instead
+        if verif.status == Status::Revoked {
+            panic_with_error!(&env, PassportError::InvalidStatusTransition);
+        }
+        let totals = refresh_score_totals(&env, &wallet);
+        let old_score = effective_score(&env, &wallet, &totals);
+        let prev = verif.clone();
+        verif.timestamp = env.ledger().timestamp();
+        verif.expires_at = expires_at;
+        verif.issuer = issuer;
+        let quorum_reached = add_confirmation(&env, &mut verif);
+        write_verif(&env, &wallet, &verif);
+        let action = if quorum_reached {
+            HistoryAction::StatusChanged
+        } else {
+            HistoryAction::Renewed
+        };
+        record_history(&env, &wallet, &verif, action, &verif.issuer);
+        if quorum_reached {
+            user.score = safe_add_i32(user.score, contribution(&verif)).unwrap_or_else(|e| {
+                panic_with_error!(&env, e);
+            });
+            write_user(&env, &user);
+        }
+
+        publish(
+            &env,
+            "VerificationRenewed",
+            Event::VerificationRenewed(wallet.clone(), vtype.clone(), expires_at),
+        );
+        if quorum_reached {
+            publish(
+                &env,
+                "VerificationStatusChanged",
+                Event::VerificationStatusChanged(
+                    wallet.clone(),
+                    vtype,
+                    Status::Pending,
+                    Status::Approved,
+                ),
+            );
+        }
 
-        let mut old_points = 0i32;
-        let mut prev_status: Option<Status> = None;
-        let mut found_idx: Option<usize> = None;
+        let effective = rescore(&env, &wallet, totals, Some(&prev), &verif, old_score);
+        on_score_written(&env, &wallet, old_score, effective);
+        effective
+    }
 
-        for (idx, v) in verifs.iter().enumerate() {
-            if v.vtype == vtype {
-                old_points = v.points;
-                prev_status = Some(v.status.clone()); 
-                found_idx = Some(idx);
-                break;
-            }
+    pub fn approve_verification(
+        env: Env,
+        reviewer: Address,
+        wallet: Address,
+        vtype: VerificationType,
+    ) -> i32 {
+        set_status(&env, &reviewer, &wallet, &vtype, Status::Approved)
+    }
+
+    pub fn reject_verification(
+        env: Env,
+        reviewer: Address,
+        wallet: Address,
+        vtype: VerificationType,
+    ) -> i32 {
+        set_status(&env, &reviewer, &wallet, &vtype, Status::Rejected)
+    }
+
+    pub fn reset_to_pending(
+        env: Env,
+        reviewer: Address,
+        wallet: Address,
+        vtype: VerificationType,
+    ) -> i32 {
+        set_status(&env, &reviewer, &wallet, &vtype, Status::Pending)
+    }
+
+    // Only an issuer that confirmed the current record (one of its attesters)
+    // can revoke it, even after losing its scope. The record is kept as
+    // `Revoked` and stops counting.
+    pub fn revoke_verification(
+        env: Env,
+        issuer: Address,
+        wallet: Address,
+        vtype: VerificationType,
+        reason: Symbol,
+    ) -> i32 {
+        issuer.require_auth();
+        let mut user = match read_user(&env, &wallet) {
+            Some(u) => u,
+            None => panic_with_error!(&env, PassportError::NotRegistered),
+        };
+
+        let mut verif = match read_verif(&env, &wallet, &vtype) {
+            Some(v) => v,
+            None => panic_with_error!(&env, PassportError::VerificationNotFound),
+        };
+        if !verif.attesters.contains(&issuer) {
+            panic_with_error!(&env, PassportError::Unauthorized);
         }
+        if verif.status == Status::Revoked {
+            panic_with_error!(&env, PassportError::InvalidStatusTransition);
+        }
+        let totals = refresh_score_totals(&env, &wallet);
+        let old_score = effective_score(&env, &wallet, &totals);
+        let prev = verif.clone();
 
-        let score_minus_old = safe_sub_i32(user.score, old_points).unwrap_or_else(|e| {
-            panic_with_error!(&env, e);
-        });
-        let new_score = safe_add_i32(score_minus_old, points).unwrap_or_else(|e| {
+        user.score = safe_sub_i32(user.score, contribution(&verif)).unwrap_or_else(|e| {
             panic_with_error!(&env, e);
         });
+        let now = env.ledger().timestamp();
+        verif.status = Status::Revoked;
+        write_verif(&env, &wallet, &verif);
+        record_history(&env, &wallet, &verif, HistoryAction::Revoked, &issuer);
+        write_user(&env, &user);
+
+        let key = DataKey::Revocation(wallet.clone(), vtype.clone());
+        env.storage().persistent().set(
+            &key,
+            &Revocation {
+                issuer: issuer.clone(),
+                reason: reason.clone(),
+                timestamp: now,
+            },
+        );
+        extend_persistent(&env, &key);
+
+        publish(
+            &env,
+            "VerificationRevoked",
+            Event::VerificationRevoked(wallet.clone(), vtype, issuer, reason),
+        );
+
+        let effective = rescore(&env, &wallet, totals, Some(&prev), &verif, old_score);
+        on_score_written(&env, &wallet, old_score, effective);
+        effective
+    }
+
+    // ===== Flags and penalties =====
 
-        let issuer = wallet.clone();
-        let new_verif = Verification {
-            vtype: vtype.clone(),
-            points,
-            timestamp: now,
-            issuer,
-            status: prev_status.unwrap_or(Status::Pending), 
+    // Records a negative attestation (fraud flag, sybil report) that
+    // subtracts `penalty` from the score until lifted. Flagging again with
+    // the same reason replaces the penalty.
+    pub fn flag_wallet(
+        env: Env,
+        issuer: Address,
+        wallet: Address,
+        reason: Symbol,
+        penalty: i32,
+    ) -> i32 {
+        require_not_paused(&env, PauseSwitch::VerificationWrites);
+        issuer.require_auth();
+        if !is_registered_issuer(&env, &issuer) {
+            panic_with_error!(&env, PassportError::IssuerNotRegistered);
+        }
+        if penalty <= 0 {
+            panic_with_error!(&env, PassportError::InvalidPoints);
+        }
+        let mut user = match read_user(&env, &wallet) {
+            Some(u) => u,
+            None => panic_with_error!(&env, PassportError::NotRegistered),
         };
 
-        match found_idx {
-            Some(i) => verifs.set(i as u32, new_verif),
+        let totals = refresh_score_totals(&env, &wallet);
+        let old_score = effective_score(&env, &wallet, &totals);
+        let mut flags = read_flags(&env, &wallet);
+        let flag = Flag {
+            issuer: issuer.clone(),
+            reason: reason.clone(),
+            penalty,
+            timestamp: env.ledger().timestamp(),
+        };
+        let old_penalty = match find_flag(&flags, &issuer, &reason) {
+            Some(i) => {
+                let old = flags.get(i).unwrap().penalty;
+                flags.set(i, flag);
+                old
+            }
             None => {
-                if user.ver_count >= MAX_VERIFICATIONS_PER_USER {
-                    panic_with_error!(&env, PassportError::TooManyVerifications);
+                if flags.len() >= MAX_FLAGS_PER_USER {
+                    panic_with_error!(&env, PassportError::TooManyFlags);
                 }
-                verifs.push_back(new_verif);
-                user.ver_count += 1;
+                flags.push_back(flag);
+                0
             }
+        };
+
+        let score_plus_old = safe_add_i32(user.score, old_penalty).unwrap_or_else(|e| {
+            panic_with_error!(&env, e);
+        });
+        user.score = safe_sub_i32(score_plus_old, penalty).unwrap_or_else(|e| {
+            panic_with_error!(&env, e);
+        });
+        write_user(&env, &user);
+        write_flags(&env, &wallet, &flags);
+
+        publish(
+            &env,
+            "WalletFlagged",
+            Event::WalletFlagged(wallet.clone(), issuer, reason, penalty),
+        );
+
+        let effective = effective_score(&env, &wallet, &totals);
+        on_score_written(&env, &wallet, old_score, effective);
+        effective
+    }
+
+    // Lifted by the issuer that raised the flag, or by the admin.
+    pub fn lift_flag(
+        env: Env,
+        caller: Address,
+        wallet: Address,
+        issuer: Address,
+        reason: Symbol,
+    ) -> i32 {
+        require_not_paused(&env, PauseSwitch::VerificationWrites);
+        caller.require_auth();
+        if caller != issuer && caller != read_admin(&env) {
+            panic_with_error!(&env, PassportError::Unauthorized);
         }
+        let mut user = match read_user(&env, &wallet) {
+            Some(u) => u,
+            None => panic_with_error!(&env, PassportError::NotRegistered),
+        };
+
+        let totals = refresh_score_totals(&env, &wallet);
+        let old_score = effective_score(&env, &wallet, &totals);
+        let mut flags = read_flags(&env, &wallet);
+        let idx = match find_flag(&flags, &issuer, &reason) {
+            Some(i) => i,
+            None => panic_with_error!(&env, PassportError::FlagNotFound),
+        };
+        let penalty = flags.get(idx).unwrap().penalty;
+        flags.remove(idx);
 
-        user.score = new_score;
+        user.score = safe_add_i32(user.score, penalty).unwrap_or_else(|e| {
+            panic_with_error!(&env, e);
+        });
         write_user(&env, &user);
-        write_verifs(&env, &wallet, &verifs);
+        write_flags(&env, &wallet, &flags);
 
-          env.events().publish(
-              (Symbol::new(&env, "passport"), Symbol::new(&env, "VerificationUpserted")),
-              Event::VerificationUpserted(wallet, vtype, old_points, points, new_score),
-          );
+        publish(
+            &env,
+            "FlagLifted",
+            Event::FlagLifted(wallet.clone(), issuer, reason, caller),
+        );
 
-        new_score
+        let effective = effective_score(&env, &wallet, &totals);
+        on_score_written(&env, &wallet, old_score, effective);
+        effective
+    }
+
+    // Active flags, listed apart from verifications.
+    pub fn get_flags(env: Env, wallet: Address) -> Vec<Flag> {
+        if read_user(&env, &wallet).is_none() {
+            panic_with_error!(&env, PassportError::NotRegistered);
+        }
+        read_flags(&env, &wallet)
+    }
+
+    pub fn is_revoked(env: Env, wallet: Address, vtype: VerificationType) -> bool {
+        require_current_schema(&env);
+        env.storage()
+            .persistent()
+            .has(&DataKey::Revocation(wallet, vtype))
+    }
+
+    pub fn get_revocation(env: Env, wallet: Address, vtype: VerificationType) -> Option<Revocation> {
+        require_current_schema(&env);
+        env.storage()
+            .persistent()
+            .get(&DataKey::Revocation(wallet, vtype))
     }
 
     pub fn update_profile(env: Env, wallet: Address, name: String, surnames: String) {
+        require_not_paused(&env, PauseSwitch::ProfileUpdates);
         wallet.require_auth();
 
         let mut user = match read_user(&env, &wallet) {
@@ -171,6 +1967,56 @@ impl StellarPassport {
     }
 }
 
+// SEP-41 shape so wallets and explorers can show the passport as a token.
+// It is soulbound: every registered wallet holds exactly one, and anything
+// that would move or burn it fails.
+#[contractimpl]
+impl TokenInterface for StellarPassport {
+    fn allowance(_env: Env, _from: Address, _spender: Address) -> i128 {
+        0
+    }
+
+    fn approve(env: Env, _from: Address, _spender: Address, _amount: i128, _expiration_ledger: u32) {
+        panic_with_error!(&env, PassportError::NonTransferable);
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        if read_user(&env, &id).is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn transfer(env: Env, _from: Address, _to: MuxedAddress, _amount: i128) {
+        panic_with_error!(&env, PassportError::NonTransferable);
+    }
+
+    fn transfer_from(env: Env, _spender: Address, _from: Address, _to: Address, _amount: i128) {
+        panic_with_error!(&env, PassportError::NonTransferable);
+    }
+
+    fn burn(env: Env, _from: Address, _amount: i128) {
+        panic_with_error!(&env, PassportError::NonTransferable);
+    }
+
+    fn burn_from(env: Env, _spender: Address, _from: Address, _amount: i128) {
+        panic_with_error!(&env, PassportError::NonTransferable);
+    }
+
+    fn decimals(_env: Env) -> u32 {
+        0
+    }
+
+    fn name(env: Env) -> String {
+        String::from_str(&env, "Stellar Passport")
+    }
+
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, "PASSPORT")
+    }
+}
+
 // Include unit tests from `src/test.rs`
 #[cfg(test)]
 mod test;
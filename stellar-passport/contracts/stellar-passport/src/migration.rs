@@ -0,0 +1,310 @@
+use soroban_sdk::{contracttype, panic_with_error, Address, Env, Map, Symbol, Val, Vec};
+
+use crate::errors::PassportError;
+use crate::types::*;
+use crate::scoring::read_policy;
+use crate::{
+    contribution, extend_persistent, publish, record_checkpoint, score_totals_of, score_with_policy,
+};
+
+/// Storage layout understood by this code.
+/// 1: users and verifications in instance storage.
+/// 2: users and verifications in persistent storage, wallet index.
+/// 3: `Verification.expires_at`.
+/// 4: one entry per verification, with an index of types per wallet.
+/// 5: `Verification.attesters`.
+/// 6: score checkpoints.
+/// 7: score totals per wallet.
+pub const SCHEMA_VERSION: u32 = 7;
+
+/// `Verification` as stored up to schema 2 (without `expires_at`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VerificationV2 {
+    pub vtype: VerificationType,
+    pub points: i32,
+    pub timestamp: u64,
+    pub issuer: Address,
+    pub status: Status,
+}
+
+/// `Verification` as stored in schemas 3 and 4 (without `attesters`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VerificationV4 {
+    pub vtype: VerificationType,
+    pub points: i32,
+    pub timestamp: u64,
+    pub expires_at: Option<u64>,
+    pub issuer: Address,
+    pub status: Status,
+}
+
+// Deployments that predate the stored version are on the v1 layout.
+pub fn read_schema_version(env: &Env) -> u32 {
+    env.storage().instance().get(&DataKey::Version).unwrap_or(1)
+}
+
+pub fn write_schema_version(env: &Env, version: u32) {
+    env.storage().instance().set(&DataKey::Version, &version);
+}
+
+// Storage written by a newer release (e.g. after rolling the code back) may
+// use a layout this code cannot decode, so refuse to touch it.
+pub fn require_known_schema(env: &Env) {
+    if read_schema_version(env) > SCHEMA_VERSION {
+        panic_with_error!(env, PassportError::SchemaTooNew);
+    }
+}
+
+// User data must not be read or written while it may still sit in an older
+// layout: it would look unregistered and could be overwritten.
+pub fn require_current_schema(env: &Env) {
+    require_known_schema(env);
+    if read_schema_version(env) < SCHEMA_VERSION {
+        panic_with_error!(env, PassportError::MigrationRequired);
+    }
+}
+
+fn read_wallet_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::WalletCount)
+        .unwrap_or(0)
+}
+
+// Appends a wallet to the registration index walked by `migrate`.
+pub fn index_wallet(env: &Env, wallet: &Address) {
+    let count = read_wallet_count(env);
+    let key = DataKey::Wallet(count);
+    env.storage().persistent().set(&key, wallet);
+    extend_persistent(env, &key);
+    env.storage()
+        .instance()
+        .set(&DataKey::WalletCount, &(count + 1));
+}
+
+// Passports written under the v1 layout, still waiting to be migrated.
+pub fn has_legacy_passport(env: &Env, wallet: &Address) -> bool {
+    env.storage()
+        .instance()
+        .has(&DataKey::User(wallet.clone()))
+}
+
+// Indexes a wallet found under the v1 layout. Once the schema has been bumped
+// no batch will reach it, so it is migrated right away instead of being left
+// in instance storage.
+pub fn index_legacy_wallet(env: &Env, wallet: &Address) {
+    index_wallet(env, wallet);
+    if read_schema_version(env) == SCHEMA_VERSION {
+        migrate_wallet(env, wallet, 1);
+    }
+}
+
+// v4 -> v5: the issuer of each record is its only attester.
+fn with_attester(env: &Env, v: VerificationV4) -> Verification {
+    Verification {
+        vtype: v.vtype,
+        points: v.points,
+        timestamp: v.timestamp,
+        expires_at: v.expires_at,
+        attesters: Vec::from_array(env, [v.issuer.clone()]),
+        issuer: v.issuer,
+        status: v.status,
+    }
+}
+
+// v2 -> v3: existing verifications never expire.
+fn upgrade_verifs_v2(env: &Env, old: Vec<VerificationV2>) -> Vec<Verification> {
+    let mut verifs = Vec::new(env);
+    for v in old.iter() {
+        let v4 = VerificationV4 {
+            vtype: v.vtype,
+            points: v.points,
+            timestamp: v.timestamp,
+            expires_at: None,
+            issuer: v.issuer,
+            status: v.status,
+        };
+        verifs.push_back(with_attester(env, v4));
+    }
+    verifs
+}
+
+// Schemas 2 and 3 kept the verifications in one persistent `Vec`. Entries
+// carrying `expires_at` are already on the v3 layout.
+fn read_persistent_verifs(env: &Env, key: &DataKey) -> Option<Vec<Verification>> {
+    let raw: Vec<Map<Symbol, Val>> = env.storage().persistent().get(key)?;
+    let expires_at = Symbol::new(env, "expires_at");
+    if raw.iter().any(|v| v.contains_key(expires_at.clone())) {
+        let old: Vec<VerificationV4> = env.storage().persistent().get(key)?;
+        return Some(Vec::from_iter(env, old.into_iter().map(|v| with_attester(env, v))));
+    }
+    let old: Vec<VerificationV2> = env.storage().persistent().get(key)?;
+    Some(upgrade_verifs_v2(env, old))
+}
+
+// v1-3 -> current: reads the wallet's entries once, from wherever schema
+// `from` kept them, and rewrites them one per type. The old entries are
+// removed once moved, so a wallet indexed twice is only migrated once.
+fn split_verifications(env: &Env, wallet: &Address, from: u32) {
+    let user_key = DataKey::User(wallet.clone());
+    let verifs_key = DataKey::Verifications(wallet.clone());
+
+    // v1 kept everything in instance storage
+    let (user, verifs): (Option<User>, Option<Vec<Verification>>) = if from < 2 {
+        let instance = env.storage().instance();
+        let user = instance.get(&user_key);
+        let verifs = instance
+            .get(&verifs_key)
+            .map(|old| upgrade_verifs_v2(env, old));
+        instance.remove(&user_key);
+        instance.remove(&verifs_key);
+        (user, verifs)
+    } else {
+        let verifs = read_persistent_verifs(env, &verifs_key);
+        env.storage().persistent().remove(&verifs_key);
+        (None, verifs)
+    };
+
+    if let Some(mut user) = user {
+        // v1 added up the points of every verification, pending ones
+        // included; only approved ones count now
+        user.score = verifs.as_ref().map_or(0, |vs| vs.iter().map(|v| contribution(&v)).sum());
+        env.storage().persistent().set(&user_key, &user);
+        extend_persistent(env, &user_key);
+    }
+    if let Some(verifs) = verifs {
+        let mut types = Vec::new(env);
+        for v in verifs.iter() {
+            let key = DataKey::Verification(wallet.clone(), v.vtype.clone());
+            types.push_back(v.vtype.clone());
+            env.storage().persistent().set(&key, &v);
+            extend_persistent(env, &key);
+        }
+        let types_key = DataKey::VerificationTypes(wallet.clone());
+        env.storage().persistent().set(&types_key, &types);
+        extend_persistent(env, &types_key);
+    }
+}
+
+// v4 -> v5: rewrites the wallet's entries with their issuer as attester.
+// Entries that already carry `attesters` are left alone, so a wallet indexed
+// twice is only migrated once.
+fn add_attesters(env: &Env, wallet: &Address) {
+    let storage = env.storage().persistent();
+    let types: Vec<VerificationType> = storage
+        .get(&DataKey::VerificationTypes(wallet.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    let attesters = Symbol::new(env, "attesters");
+    for vtype in types.iter() {
+        let key = DataKey::Verification(wallet.clone(), vtype);
+        let raw: Map<Symbol, Val> = storage.get(&key).unwrap();
+        if raw.contains_key(attesters.clone()) {
+            continue;
+        }
+        let old: VerificationV4 = storage.get(&key).unwrap();
+        storage.set(&key, &with_attester(env, old));
+        extend_persistent(env, &key);
+    }
+}
+
+// The wallet's entries, once they are in the current layout.
+fn read_entries(env: &Env, wallet: &Address) -> Vec<Verification> {
+    let storage = env.storage().persistent();
+    let types: Vec<VerificationType> = storage
+        .get(&DataKey::VerificationTypes(wallet.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    let mut verifs = Vec::new(env);
+    for vtype in types.iter() {
+        let verif: Verification = storage
+            .get(&DataKey::Verification(wallet.clone(), vtype))
+            .unwrap();
+        verifs.push_back(verif);
+    }
+    verifs
+}
+
+// v5 -> v6: score history starts at the migration, with the score relying
+// parties see (policy, point table, decay and penalties applied), not the
+// raw `User.score`. Wallets that already have checkpoints are left alone, so
+// a wallet indexed twice is only migrated once.
+fn add_first_checkpoint(env: &Env, wallet: &Address, verifs: &Vec<Verification>) {
+    let storage = env.storage().persistent();
+    if storage.has(&DataKey::CheckpointLen(wallet.clone()))
+        || !storage.has(&DataKey::User(wallet.clone()))
+    {
+        return;
+    }
+    let flags: Vec<Flag> = storage
+        .get(&DataKey::Flags(wallet.clone()))
+        .unwrap_or_else(|| Vec::new(env));
+    let score = score_with_policy(env, Map::new(env), verifs, &read_policy(env), &flags);
+    record_checkpoint(env, wallet, score);
+}
+
+// v6 -> v7: builds the score totals from the wallet's entries. Rebuilding
+// gives the same totals, so a wallet indexed twice is harmless.
+fn build_score_totals(env: &Env, wallet: &Address, verifs: &Vec<Verification>) {
+    let key = DataKey::ScoreTotals(wallet.clone());
+    env.storage().persistent().set(&key, &score_totals_of(env, verifs));
+    extend_persistent(env, &key);
+}
+
+// Brings one wallet from schema `from` to the current layout.
+fn migrate_wallet(env: &Env, wallet: &Address, from: u32) {
+    if from < 4 {
+        split_verifications(env, wallet, from);
+    } else if from < 5 {
+        add_attesters(env, wallet);
+    }
+    if from < 7 {
+        let verifs = read_entries(env, wallet);
+        build_score_totals(env, wallet, &verifs);
+        // After the totals, so the checkpoint matches the score they give
+        if from < 6 {
+            add_first_checkpoint(env, wallet, &verifs);
+        }
+    }
+}
+
+/// Migrates up to `batch_size` wallets from the stored cursor and returns how
+/// many are left. The schema version is bumped once every wallet is done.
+pub fn migrate_batch(env: &Env, batch_size: u32) -> u32 {
+    if batch_size == 0 {
+        panic_with_error!(env, PassportError::InvalidBatchSize);
+    }
+
+    require_known_schema(env);
+    let from = read_schema_version(env);
+    if from == SCHEMA_VERSION {
+        return 0;
+    }
+
+    let count = read_wallet_count(env);
+    let cursor: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::MigrationCursor)
+        .unwrap_or(0);
+    let end = count.min(cursor.saturating_add(batch_size));
+
+    for i in cursor..end {
+        let wallet: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Wallet(i))
+            .unwrap();
+        migrate_wallet(env, &wallet, from);
+    }
+
+    if end < count {
+        env.storage().instance().set(&DataKey::MigrationCursor, &end);
+        return count - end;
+    }
+
+    env.storage().instance().remove(&DataKey::MigrationCursor);
+    write_schema_version(env, SCHEMA_VERSION);
+    publish(env, "Migrated", Event::Migrated(from, SCHEMA_VERSION));
+    0
+}
@@ -0,0 +1,82 @@
+use soroban_sdk::{panic_with_error, Address, Env, Vec};
+
+use crate::errors::PassportError;
+use crate::types::*;
+use crate::{current_score, is_expired, read_flags};
+
+// Bounds on a requirement tree, so a caller cannot make evaluation
+// arbitrarily expensive.
+pub const MAX_REQUIREMENT_DEPTH: u32 = 5;
+pub const MAX_REQUIREMENT_NODES: u32 = 32;
+
+fn count_nodes(env: &Env, req: &Requirement, depth: u32, nodes: &mut u32) {
+    *nodes += 1;
+    if depth > MAX_REQUIREMENT_DEPTH || *nodes > MAX_REQUIREMENT_NODES {
+        panic_with_error!(env, PassportError::InvalidRequirement);
+    }
+    match req {
+        Requirement::And(children) | Requirement::Or(children) => {
+            if children.is_empty() {
+                panic_with_error!(env, PassportError::InvalidRequirement);
+            }
+            for child in children.iter() {
+                count_nodes(env, &child, depth + 1, nodes);
+            }
+        }
+        Requirement::Not(children) => {
+            if children.len() != 1 {
+                panic_with_error!(env, PassportError::InvalidRequirement);
+            }
+            count_nodes(env, &children.get(0).unwrap(), depth + 1, nodes);
+        }
+        _ => {}
+    }
+}
+
+// The whole tree is checked up front, even the branches evaluation would
+// short-circuit.
+pub fn validate(env: &Env, req: &Requirement) {
+    let mut nodes = 0;
+    count_nodes(env, req, 1, &mut nodes);
+}
+
+fn find(verifs: &Vec<Verification>, vtype: &VerificationType) -> Option<Verification> {
+    verifs.iter().find(|v| v.vtype == *vtype && v.status != Status::Revoked)
+}
+
+// `score` is computed at most once, the first time a `MinScore` leaf needs it.
+pub fn evaluate(
+    env: &Env,
+    req: &Requirement,
+    wallet: &Address,
+    verifs: &Vec<Verification>,
+    score: &mut Option<i32>,
+) -> bool {
+    let now = env.ledger().timestamp();
+    match req {
+        Requirement::And(children) => {
+            children.iter().all(|c| evaluate(env, &c, wallet, verifs, score))
+        }
+        Requirement::Or(children) => {
+            children.iter().any(|c| evaluate(env, &c, wallet, verifs, score))
+        }
+        Requirement::Not(children) => {
+            !evaluate(env, &children.get(0).unwrap(), wallet, verifs, score)
+        }
+        Requirement::HasType(vtype) => find(verifs, vtype).is_some(),
+        Requirement::Approved(vtype) => {
+            matches!(find(verifs, vtype), Some(v) if v.status == Status::Approved && !is_expired(&v, now))
+        }
+        Requirement::NotExpired(vtype) => {
+            matches!(find(verifs, vtype), Some(v) if !is_expired(&v, now))
+        }
+        Requirement::IssuedBy(vtype, issuer) => {
+            matches!(find(verifs, vtype), Some(v) if v.issuer == *issuer)
+        }
+        Requirement::MinScore(min) => {
+            let score = *score.get_or_insert_with(|| current_score(env, wallet));
+            score >= *min
+        }
+        Requirement::NoActiveFlags => read_flags(env, wallet).is_empty(),
+    }
+}
@@ -0,0 +1,172 @@
+use soroban_sdk::{panic_with_error, symbol_short, Env, Map, Symbol, Vec};
+
+use crate::errors::PassportError;
+use crate::types::*;
+
+// Basis points: 10_000 = 1x.
+pub const BPS: u32 = 10_000;
+
+// Largest category weight, 10x: a larger one could overflow the score.
+pub const MAX_WEIGHT: u32 = 10 * BPS;
+
+// 2^(-k/16) for k in 0..=16, in Q32 fixed point.
+const HALF_POWERS_Q32: [u64; 17] = [
+    4294967296, 4112874773, 3938502376, 3771522796, 3611622603, 3458501653, 3311872529,
+    3171459999, 3037000500, 2908241642, 2784941738, 2666869345, 2553802834, 2445529972,
+    2341847524, 2242560872, 2147483648,
+];
+
+// Without a stored policy every approved, non-expired verification counts
+// its full points, i.e. the plain sum.
+fn default_policy(env: &Env) -> ScoringPolicy {
+    ScoringPolicy {
+        categories: Map::new(env),
+        weights: Map::new(env),
+        caps: Map::new(env),
+        decay_bps: BPS,
+    }
+}
+
+pub fn read_policy(env: &Env) -> ScoringPolicy {
+    env.storage()
+        .instance()
+        .get(&DataKey::ScoringPolicy)
+        .unwrap_or_else(|| default_policy(env))
+}
+
+pub fn validate_policy(env: &Env, policy: &ScoringPolicy) {
+    let caps_valid = policy.caps.values().iter().all(|cap| cap >= 0);
+    let weights_valid = policy.weights.values().iter().all(|w| w <= MAX_WEIGHT);
+    if policy.decay_bps > BPS || !caps_valid || !weights_valid {
+        panic_with_error!(env, PassportError::InvalidConfig);
+    }
+}
+
+pub fn read_half_lives(env: &Env) -> Map<VerificationType, u64> {
+    env.storage()
+        .instance()
+        .get(&DataKey::HalfLives)
+        .unwrap_or_else(|| Map::new(env))
+}
+
+// 2^(-age / half_life) in Q32: whole half-lives are shifts, the remaining
+// fraction is interpolated linearly between sixteenths of the table. Half-lives
+// past 2^31 seconds are scaled down so the interpolation stays within u64.
+fn decay_factor_q32(age: u64, half_life: u64) -> u64 {
+    let halvings = age / half_life;
+    if halvings >= 64 {
+        return 0;
+    }
+    let shift = (64 - half_life.leading_zeros()).saturating_sub(31);
+    let (rem, half_life) = ((age % half_life) >> shift, half_life >> shift);
+    let pos = rem * 16;
+    let (step, rem) = ((pos / half_life) as usize, pos % half_life);
+    let (hi, lo) = (HALF_POWERS_Q32[step], HALF_POWERS_Q32[step + 1]);
+    (hi - (hi - lo) * rem / half_life) >> halvings
+}
+
+/// Points left of a verification issued at `timestamp` once it has decayed
+/// with `half_life` (seconds) until `now`. Rounded down.
+pub fn decayed_points(points: i32, timestamp: u64, now: u64, half_life: u64) -> i32 {
+    let age = now.saturating_sub(timestamp);
+    ((points as i64 * decay_factor_q32(age, half_life) as i64) >> 32) as i32
+}
+
+// Tier name reported below the first threshold, or when no tiers are set.
+pub const NO_TIER: Symbol = symbol_short!("none");
+
+pub fn read_tiers(env: &Env) -> Vec<Tier> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Tiers)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+pub fn validate_tiers(env: &Env, tiers: &Vec<Tier>) {
+    let mut previous: Option<i32> = None;
+    for tier in tiers.iter() {
+        if matches!(previous, Some(p) if tier.min_score <= p) || tier.name == NO_TIER {
+            panic_with_error!(env, PassportError::InvalidConfig);
+        }
+        previous = Some(tier.min_score);
+    }
+}
+
+// Highest tier whose threshold `score` reaches.
+pub fn tier_for(tiers: &Vec<Tier>, score: i32) -> Symbol {
+    let mut reached = NO_TIER;
+    for tier in tiers.iter() {
+        if score < tier.min_score {
+            break;
+        }
+        reached = tier.name;
+    }
+    reached
+}
+
+// Category a verification falls in when the policy does not map its type:
+// built-in types get their own, all `Custom` types share one.
+pub fn category_of(policy: &ScoringPolicy, vtype: &VerificationType) -> Symbol {
+    if let Some(category) = policy.categories.get(vtype.clone()) {
+        return category;
+    }
+    match vtype {
+        VerificationType::Over18 => symbol_short!("over18"),
+        VerificationType::Twitter => symbol_short!("twitter"),
+        VerificationType::GitHub => symbol_short!("github"),
+        VerificationType::BrightID => symbol_short!("brightid"),
+        VerificationType::WorldID => symbol_short!("worldid"),
+        VerificationType::Custom(_) => symbol_short!("custom"),
+    }
+}
+
+fn insert_desc(points: &mut Vec<i32>, value: i32) {
+    let idx = points.iter().position(|p| p < value).unwrap_or(points.len() as usize);
+    points.insert(idx as u32, value);
+}
+
+fn to_i32(env: &Env, value: i64) -> i32 {
+    i32::try_from(value).unwrap_or_else(|_| panic_with_error!(env, PassportError::Overflow))
+}
+
+/// Score of `contributions` (type, points) under `policy`, on top of the
+/// points in `sums` already added up per category (only meaningful without
+/// diminishing returns). Per category, the highest contribution counts in
+/// full and each following one is multiplied once more by `decay_bps`; the
+/// sum is then weighted and capped.
+pub fn compute_score(
+    env: &Env,
+    mut sums: Map<Symbol, i64>,
+    contributions: &Vec<(VerificationType, i32)>,
+    policy: &ScoringPolicy,
+) -> i32 {
+    let mut by_category: Map<Symbol, Vec<i32>> = Map::new(env);
+    for (vtype, points) in contributions.iter() {
+        let category = category_of(policy, &vtype);
+        let mut points_in = by_category.get(category.clone()).unwrap_or_else(|| Vec::new(env));
+        insert_desc(&mut points_in, points);
+        by_category.set(category, points_in);
+    }
+
+    let bps = BPS as i64;
+    for (category, points_in) in by_category.iter() {
+        let mut sum = sums.get(category.clone()).unwrap_or(0);
+        let mut factor = bps;
+        for points in points_in.iter() {
+            sum += points as i64 * factor / bps;
+            factor = factor * policy.decay_bps as i64 / bps;
+        }
+        sums.set(category, sum);
+    }
+
+    let mut total = 0i32;
+    for (category, sum) in sums.iter() {
+        let weight = policy.weights.get(category.clone()).unwrap_or(BPS);
+        let mut sum = sum.saturating_mul(weight as i64) / bps;
+        if let Some(cap) = policy.caps.get(category) {
+            sum = sum.min(cap as i64);
+        }
+        total = to_i32(env, total as i64 + sum);
+    }
+    total
+}
@@ -3,18 +3,146 @@
 extern crate alloc;
 
 use alloc::format;
-use soroban_sdk::{testutils::Address as _, symbol_short, Address, Env, String, Symbol};
-
+use ed25519_dalek::Signer as _;
+use rand::{rngs::StdRng, SeedableRng};
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Events as _, Ledger as _},
+    map, symbol_short, vec, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, String, Symbol, TryFromVal,
+    Vec,
+};
+
+use crate::errors::PassportError;
 use crate::{StellarPassport, StellarPassportClient};
-use crate::types::{VerificationType, Status};
+use crate::migration::{VerificationV2, VerificationV4, SCHEMA_VERSION};
+use crate::types::{
+    Attestation, DataKey, Event, Flag, HistoryAction, IssuerKeyRecord, KeyAlgorithm, PauseSwitch, PointRule, Revocation,
+    ScoringPolicy,
+    Requirement, Tier, TtlConfig, User, VerificationType, Status,
+};
+
+fn last_event(env: &Env) -> Event {
+    let (_, _, data) = env.events().all().last().unwrap();
+    Event::try_from_val(env, &data).unwrap()
+}
+
+fn setup(env: &Env) -> (StellarPassportClient<'_>, Address) {
+    let admin = Address::generate(env);
+    let contract_id = env.register(StellarPassport, (&admin,));
+    let client = StellarPassportClient::new(env, &contract_id);
+
+    let issuer = Address::generate(env);
+    client.add_issuer(&issuer);
+    for vtype in [
+        VerificationType::Over18,
+        VerificationType::Twitter,
+        VerificationType::GitHub,
+        VerificationType::BrightID,
+        VerificationType::WorldID,
+    ] {
+        client.grant_scope(&issuer, &vtype);
+    }
+    (client, issuer)
+}
+
+// Issuer key generated off-chain, on either supported curve.
+enum TestSigner {
+    Ed25519(ed25519_dalek::SigningKey),
+    Secp256r1(p256::ecdsa::SigningKey),
+}
+
+impl TestSigner {
+    fn ed25519(seed: u64) -> Self {
+        TestSigner::Ed25519(ed25519_dalek::SigningKey::generate(&mut StdRng::seed_from_u64(seed)))
+    }
+
+    fn secp256r1(seed: u64) -> Self {
+        TestSigner::Secp256r1(p256::ecdsa::SigningKey::random(&mut StdRng::seed_from_u64(seed)))
+    }
+
+    fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            TestSigner::Ed25519(_) => KeyAlgorithm::Ed25519,
+            TestSigner::Secp256r1(_) => KeyAlgorithm::Secp256r1,
+        }
+    }
+
+    fn public_key(&self, env: &Env) -> Bytes {
+        match self {
+            TestSigner::Ed25519(k) => Bytes::from_slice(env, &k.verifying_key().to_bytes()),
+            TestSigner::Secp256r1(k) => {
+                Bytes::from_slice(env, k.verifying_key().to_encoded_point(false).as_bytes())
+            }
+        }
+    }
+
+    fn key_id(&self, env: &Env) -> BytesN<32> {
+        match self {
+            TestSigner::Ed25519(_) => self.public_key(env).try_into().unwrap(),
+            TestSigner::Secp256r1(_) => env.crypto().sha256(&self.public_key(env)).to_bytes(),
+        }
+    }
+
+    fn register(&self, env: &Env, client: &StellarPassportClient, issuer: &Address) -> BytesN<32> {
+        client.add_issuer_key(issuer, &self.algorithm(), &self.public_key(env))
+    }
+
+    // p256 hashes the message with sha256 before signing. The host only
+    // accepts low-S signatures.
+    fn sign(&self, env: &Env, payload: &Bytes) -> BytesN<64> {
+        let message = payload.to_alloc_vec();
+        match self {
+            TestSigner::Ed25519(k) => BytesN::from_array(env, &k.sign(&message).to_bytes()),
+            TestSigner::Secp256r1(k) => {
+                let signature: p256::ecdsa::Signature = k.sign(&message);
+                let signature = signature.normalize_s().unwrap_or(signature);
+                BytesN::from_array(env, &signature.to_bytes().into())
+            }
+        }
+    }
+}
+
+fn sign_attestation(env: &Env, signer: &TestSigner, attestation: &Attestation) -> (Bytes, BytesN<64>) {
+    let payload = attestation.clone().to_xdr(env);
+    let signature = signer.sign(env, &payload);
+    (payload, signature)
+}
+
+// WebAuthn assertion over `payload` the way a browser builds it: the
+// challenge is the base64url sha256 of the payload and the passkey signs
+// `authenticator_data || sha256(client_data_json)`.
+fn passkey_assertion(
+    env: &Env,
+    signer: &TestSigner,
+    payload: &Bytes,
+    kind: &str,
+    flags: u8,
+) -> (Bytes, Bytes, BytesN<64>) {
+    use base64::Engine as _;
+    let digest = env.crypto().sha256(payload).to_array();
+    let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest);
+    let client_data = format!(
+        r#"{{"type":"{kind}","challenge":"{challenge}","origin":"https://wallet.example","crossOrigin":false}}"#
+    );
+    let client_data_json = Bytes::from_slice(env, client_data.as_bytes());
+
+    // rpId hash, flags, signature counter
+    let mut auth = [0u8; 37];
+    auth[..32].copy_from_slice(&env.crypto().sha256(&Bytes::from_slice(env, b"wallet.example")).to_array());
+    auth[32] = flags;
+    auth[36] = 1;
+    let authenticator_data = Bytes::from_slice(env, &auth);
+
+    let mut signed = authenticator_data.clone();
+    signed.extend_from_array(&env.crypto().sha256(&client_data_json).to_array());
+    (authenticator_data, client_data_json, signer.sign(env, &signed))
+}
 
 #[test]
 fn end_to_end_flow() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
 
@@ -26,17 +154,26 @@ fn end_to_end_flow() {
     );
     assert_eq!(client.get_score(&alice), 0);
 
-    // Primera verificación
-    let s1 = client.upsert_verification(&alice, &VerificationType::Over18, &10);
+    // Primera verificación (pendiente de revisión, aún no suma)
+    let s1 = client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    assert_eq!(s1, 0);
+    assert_eq!(client.get_pending_points(&alice), 10);
+
+    // Aprobación
+    let s1 = client.approve_verification(&issuer, &alice, &VerificationType::Over18);
     assert_eq!(s1, 10);
+    assert_eq!(client.get_pending_points(&alice), 0);
 
     // Actualizar misma verificación
-    let s2 = client.upsert_verification(&alice, &VerificationType::Over18, &25);
+    let s2 = client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &25, &None);
     assert_eq!(s2, 25);
 
     // Otra verificación
-    let s3 =
-        client.upsert_verification(&alice, &VerificationType::Custom(symbol_short!("twitter")), &15);
+    let custom = VerificationType::Custom(symbol_short!("twitter"));
+    client.grant_scope(&issuer, &custom);
+    let s3 = client.upsert_verification(&issuer, &alice, &custom, &15, &None);
+    assert_eq!(s3, 25);
+    let s3 = client.approve_verification(&issuer, &alice, &custom);
     assert_eq!(s3, 40);
 
     let verifs = client.get_verifications(&alice);
@@ -49,8 +186,7 @@ fn register_and_duplicate_register_error() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, _) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(
@@ -72,8 +208,7 @@ fn upsert_flow_and_score_math() {
     let env = Env::default();
     env.mock_all_auths();
 
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "A"), &String::from_str(&env, "B"));
@@ -81,16 +216,22 @@ fn upsert_flow_and_score_math() {
     assert_eq!(client.get_score(&alice), 0);
 
     // first upsert
-    assert_eq!(client.upsert_verification(&alice, &VerificationType::Over18, &5), 5);
+    assert_eq!(client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &5, &None), 0);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::Over18), 5);
     assert_eq!(client.get_score(&alice), 5);
 
     // update same type
-    assert_eq!(client.upsert_verification(&alice, &VerificationType::Over18, &7), 7);
+    assert_eq!(client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &7, &None), 7);
     assert_eq!(client.get_score(&alice), 7);
 
     // add another type
+    client.grant_scope(&issuer, &VerificationType::Custom(symbol_short!("x")));
+    assert_eq!(
+        client.upsert_verification(&issuer, &alice, &VerificationType::Custom(symbol_short!("x")), &3, &None),
+        7
+    );
     assert_eq!(
-        client.upsert_verification(&alice, &VerificationType::Custom(symbol_short!("x")), &3),
+        client.approve_verification(&issuer, &alice, &VerificationType::Custom(symbol_short!("x"))),
         10
     );
     assert_eq!(client.get_score(&alice), 10);
@@ -101,8 +242,7 @@ fn upsert_flow_and_score_math() {
 fn get_score_not_registered_should_panic() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, _) = setup(&env);
 
     let bob = Address::generate(&env);
 
@@ -115,27 +255,26 @@ fn get_score_not_registered_should_panic() {
 fn upsert_not_registered_should_panic() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let bob = Address::generate(&env);
-    let _ = client.upsert_verification(&bob, &VerificationType::Over18, &1);
+    let _ = client.upsert_verification(&issuer, &bob, &VerificationType::Over18, &1, &None);
 }
 
 #[test]
 fn max_verifications_limit() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "A"), &String::from_str(&env, "B"));
 
     // fill up to the limit (assuming 50)
     for i in 0..50 {
-        let sym = Symbol::new(&env, &format!("t{}", i));
-        let _ = client.upsert_verification(&alice, &VerificationType::Custom(sym), &1);
+        let vtype = VerificationType::Custom(Symbol::new(&env, &format!("t{}", i)));
+        client.grant_scope(&issuer, &vtype);
+        let _ = client.upsert_verification(&issuer, &alice, &vtype, &1, &None);
     }
 }
 
@@ -144,66 +283,288 @@ fn max_verifications_limit() {
 fn max_verifications_limit_overflow_should_panic() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "A"), &String::from_str(&env, "B"));
 
     for i in 0..50 {
-        let sym = Symbol::new(&env, &format!("t{}", i));
-        let _ = client.upsert_verification(&alice, &VerificationType::Custom(sym), &1);
+        let vtype = VerificationType::Custom(Symbol::new(&env, &format!("t{}", i)));
+        client.grant_scope(&issuer, &vtype);
+        let _ = client.upsert_verification(&issuer, &alice, &vtype, &1, &None);
     }
 
     // one more must panic
-    let sym = Symbol::new(&env, "overflow");
-    let _ = client.upsert_verification(&alice, &VerificationType::Custom(sym), &1);
+    let vtype = VerificationType::Custom(Symbol::new(&env, "overflow"));
+    client.grant_scope(&issuer, &vtype);
+    let _ = client.upsert_verification(&issuer, &alice, &vtype, &1, &None);
 }
 
 // ===== NEW SECURITY TESTS =====
 
 #[test]
-fn test_self_issued_verification_flaw() {
+fn test_self_issued_verification_rejected() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, _) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
-    // This test demonstrates the critical flaw: users can self-issue verifications
-    // with arbitrary points, completely undermining the trust system
-    let verifs = client.get_verifications(&alice);
-    assert_eq!(verifs.len(), 0);
+    // Alice is not a trusted issuer, so she cannot grant herself points
+    let res = client.try_upsert_verification(&alice, &alice, &VerificationType::Over18, &1000, &None);
+    assert_eq!(res, Err(Ok(PassportError::IssuerNotRegistered.into())));
+
+    assert_eq!(client.get_score(&alice), 0);
+    assert_eq!(client.get_verifications(&alice).len(), 0);
+}
+
+#[test]
+fn test_verification_records_issuer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
-    // Alice can give herself 1000 points for any verification type
-    let score = client.upsert_verification(&alice, &VerificationType::Over18, &1000);
-    assert_eq!(score, 1000);
+    let score = client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &1000, &None);
+    assert_eq!(score, 0);
 
     let verifs = client.get_verifications(&alice);
     assert_eq!(verifs.len(), 1);
-    
-    // The verification shows Alice as the issuer (the flaw)
+
     let verif = &verifs.get(0).unwrap();
-    assert_eq!(verif.issuer, alice);
+    assert_eq!(verif.issuer, issuer);
     assert_eq!(verif.points, 1000);
     assert_eq!(verif.status, Status::Pending);
 }
 
+#[test]
+fn test_issuer_registry_add_remove() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let other = Address::generate(&env);
+    assert!(client.is_issuer(&issuer));
+    assert!(!client.is_issuer(&other));
+
+    client.add_issuer(&other);
+    assert!(client.is_issuer(&other));
+
+    client.remove_issuer(&other);
+    assert!(!client.is_issuer(&other));
+
+    // Removing an unknown issuer is an error
+    assert_eq!(
+        client.try_remove_issuer(&other),
+        Err(Ok(PassportError::IssuerNotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_removed_issuer_cannot_upsert() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    client.remove_issuer(&issuer);
+    let res = client.try_upsert_verification(&issuer, &alice, &VerificationType::Twitter, &10, &None);
+    assert_eq!(res, Err(Ok(PassportError::IssuerNotRegistered.into())));
+    assert_eq!(client.get_score(&alice), 10);
+}
+
+#[test]
+fn test_issuer_scopes_grant_and_revoke() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let kyc_vendor = Address::generate(&env);
+    let kyc = VerificationType::Custom(Symbol::new(&env, "kyc_sumsub"));
+    client.add_issuer(&kyc_vendor);
+    assert_eq!(client.issuer_scopes(&kyc_vendor).len(), 0);
+
+    client.grant_scope(&kyc_vendor, &kyc);
+    client.grant_scope(&kyc_vendor, &VerificationType::Over18);
+    // Granting twice is a no-op
+    client.grant_scope(&kyc_vendor, &kyc);
+    let scopes = client.issuer_scopes(&kyc_vendor);
+    assert_eq!(scopes.len(), 2);
+    assert_eq!(scopes.get(0).unwrap(), kyc);
+
+    client.revoke_scope(&kyc_vendor, &VerificationType::Over18);
+    let scopes = client.issuer_scopes(&kyc_vendor);
+    assert_eq!(scopes.len(), 1);
+    assert_eq!(scopes.get(0).unwrap(), kyc);
+
+    assert_eq!(
+        client.try_revoke_scope(&kyc_vendor, &VerificationType::Over18),
+        Err(Ok(PassportError::ScopeNotGranted.into()))
+    );
+}
+
+#[test]
+fn test_issuer_scope_enforced_on_upsert() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    let kyc = VerificationType::Custom(Symbol::new(&env, "kyc_sumsub"));
+    let kyc_vendor = Address::generate(&env);
+    client.add_issuer(&kyc_vendor);
+    client.grant_scope(&kyc_vendor, &kyc);
+
+    let social_bot = Address::generate(&env);
+    client.add_issuer(&social_bot);
+    client.grant_scope(&social_bot, &VerificationType::Twitter);
+    client.grant_scope(&social_bot, &VerificationType::GitHub);
+
+    client.upsert_verification(&kyc_vendor, &alice, &kyc, &50, &None);
+    client.upsert_verification(&social_bot, &alice, &VerificationType::GitHub, &10, &None);
+
+    // Each issuer is limited to its own scopes
+    assert_eq!(
+        client.try_upsert_verification(&kyc_vendor, &alice, &VerificationType::Twitter, &10, &None),
+        Err(Ok(PassportError::ScopeNotGranted.into()))
+    );
+    assert_eq!(
+        client.try_upsert_verification(&social_bot, &alice, &kyc, &50, &None),
+        Err(Ok(PassportError::ScopeNotGranted.into()))
+    );
+    assert_eq!(client.get_verifications(&alice).len(), 2);
+}
+
+#[test]
+fn test_removed_issuer_loses_scopes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    client.remove_issuer(&issuer);
+    assert_eq!(client.issuer_scopes(&issuer).len(), 0);
+
+    // Re-adding the issuer does not restore previous scopes
+    client.add_issuer(&issuer);
+    assert_eq!(client.issuer_scopes(&issuer).len(), 0);
+    assert_eq!(
+        client.try_grant_scope(&Address::generate(&env), &VerificationType::Over18),
+        Err(Ok(PassportError::IssuerNotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_constructor_sets_admin() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(StellarPassport, (&admin,));
+    let client = StellarPassportClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_admin(), admin);
+    assert_eq!(client.get_pending_admin(), None);
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let old_admin = client.get_admin();
+    let new_admin = Address::generate(&env);
+
+    client.propose_admin(&new_admin);
+    assert_eq!(env.auths()[0].0, old_admin);
+    assert_eq!(last_event(&env), Event::AdminProposed(old_admin.clone(), new_admin.clone()));
+
+    // Nothing changes until the nominee accepts
+    assert_eq!(client.get_admin(), old_admin);
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+
+    client.accept_admin();
+    assert_eq!(env.auths()[0].0, new_admin);
+    assert_eq!(last_event(&env), Event::AdminChanged(old_admin, new_admin.clone()));
+    assert_eq!(client.get_admin(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+
+    // Privileged operations now require the new admin
+    client.add_issuer(&Address::generate(&env));
+    assert_eq!(env.auths()[0].0, new_admin);
+}
+
+#[test]
+fn test_propose_admin_replaces_nominee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    client.propose_admin(&first);
+    client.propose_admin(&second);
+
+    client.accept_admin();
+    assert_eq!(env.auths()[0].0, second);
+    assert_eq!(client.get_admin(), second);
+}
+
+#[test]
+fn test_accept_admin_without_proposal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    assert_eq!(
+        client.try_accept_admin(),
+        Err(Ok(PassportError::NoPendingAdmin.into()))
+    );
+}
+
 #[test]
 #[should_panic]
-fn test_negative_points_should_panic() {
+fn test_accept_admin_requires_nominee_auth() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
+    let (client, _) = setup(&env);
+    client.propose_admin(&Address::generate(&env));
+
+    env.set_auths(&[]);
+    client.accept_admin();
+}
+
+#[test]
+#[should_panic]
+fn test_add_issuer_requires_admin_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(StellarPassport, (&admin,));
     let client = StellarPassportClient::new(&env, &contract_id);
 
+    // No auths mocked: the admin signature is missing
+    client.add_issuer(&Address::generate(&env));
+}
+
+#[test]
+#[should_panic]
+fn test_negative_points_should_panic() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
     // Negative points should panic
-    let _ = client.upsert_verification(&alice, &VerificationType::Over18, &-10);
+    let _ = client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &-10, &None);
 }
 
 #[test]
@@ -211,22 +572,20 @@ fn test_negative_points_should_panic() {
 fn test_zero_points_should_panic() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
     // Zero points should panic
-    let _ = client.upsert_verification(&alice, &VerificationType::Over18, &0);
+    let _ = client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &0, &None);
 }
 
 #[test]
 fn test_empty_string_registration_allowed() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, _) = setup(&env);
 
     let alice = Address::generate(&env);
     
@@ -241,8 +600,7 @@ fn test_empty_string_registration_allowed() {
 fn test_empty_string_profile_update_allowed() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, _) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
@@ -258,14 +616,14 @@ fn test_empty_string_profile_update_allowed() {
 fn test_unauthorized_score_access() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     let _bob = Address::generate(&env);
     
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
-    client.upsert_verification(&alice, &VerificationType::Over18, &100);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &100, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
     
     // Bob can access Alice's score without authorization (privacy concern)
     assert_eq!(client.get_score(&alice), 100);
@@ -276,51 +634,214 @@ fn test_unauthorized_score_access() {
 }
 
 #[test]
-fn test_verification_status_preservation_flaw() {
+fn test_verification_status_preserved_on_update() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
     // Add initial verification
-    client.upsert_verification(&alice, &VerificationType::Over18, &10);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
     
     let verifs = client.get_verifications(&alice);
     let initial_verif = &verifs.get(0).unwrap();
     assert_eq!(initial_verif.status, Status::Pending);
     
     // Update the same verification - status should be preserved
-    client.upsert_verification(&alice, &VerificationType::Over18, &20);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &20, &None);
     
     let verifs = client.get_verifications(&alice);
     let updated_verif = &verifs.get(0).unwrap();
     assert_eq!(updated_verif.points, 20);
     assert_eq!(updated_verif.status, Status::Pending); // Status preserved
-    
-    // This demonstrates the flaw: there's no way to change verification status
-    // once it's set, even if the verification is updated
+
+    // An approved verification stays approved when the issuer updates it
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &30, &None);
+    let verif = client.get_verifications(&alice).get(0).unwrap();
+    assert_eq!(verif.points, 30);
+    assert_eq!(verif.status, Status::Approved);
+}
+
+#[test]
+fn test_status_lifecycle_transitions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &10, &None);
+
+    let status = |client: &StellarPassportClient| client.get_verifications(&alice).get(0).unwrap().status;
+
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationStatusChanged(
+            alice.clone(),
+            VerificationType::GitHub,
+            Status::Pending,
+            Status::Approved
+        )
+    );
+    assert_eq!(status(&client), Status::Approved);
+
+    client.reset_to_pending(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(status(&client), Status::Pending);
+
+    client.reject_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(status(&client), Status::Rejected);
+
+    client.reset_to_pending(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(status(&client), Status::Pending);
+
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    client.reject_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(status(&client), Status::Rejected);
+}
+
+#[test]
+fn test_invalid_status_transitions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &10, &None);
+
+    let invalid = Err(Ok(PassportError::InvalidStatusTransition.into()));
+
+    // Pending -> Pending
+    assert_eq!(client.try_reset_to_pending(&issuer, &alice, &VerificationType::GitHub), invalid);
+
+    // Approved -> Approved
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(client.try_approve_verification(&issuer, &alice, &VerificationType::GitHub), invalid);
+
+    // Rejected -> Approved needs a re-submission first
+    client.reject_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(client.try_approve_verification(&issuer, &alice, &VerificationType::GitHub), invalid);
+    assert_eq!(client.try_reject_verification(&issuer, &alice, &VerificationType::GitHub), invalid);
+}
+
+#[test]
+fn test_resubmission_of_rejected_verification() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.reject_verification(&issuer, &alice, &VerificationType::Over18);
+
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &15, &None);
+    let verif = client.get_verifications(&alice).get(0).unwrap();
+    assert_eq!(verif.status, Status::Pending);
+
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    let verif = client.get_verifications(&alice).get(0).unwrap();
+    assert_eq!(verif.status, Status::Approved);
+}
+
+#[test]
+fn test_score_counts_only_approved_verifications() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    assert_eq!(client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None), 0);
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationUpserted(alice.clone(), VerificationType::Over18, 0, 10, 0)
+    );
+    assert_eq!(client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &20, &None), 0);
+    assert_eq!(client.get_score(&alice), 0);
+    assert_eq!(client.get_pending_points(&alice), 30);
+
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::Over18), 10);
+    assert_eq!(client.reject_verification(&issuer, &alice, &VerificationType::GitHub), 10);
+    assert_eq!(client.get_pending_points(&alice), 0);
+
+    // Updating an approved verification is reflected in the event and return value
+    assert_eq!(client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &15, &None), 15);
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationUpserted(alice.clone(), VerificationType::Over18, 10, 15, 15)
+    );
+
+    // Approved -> Rejected / Pending removes the points again
+    assert_eq!(client.reset_to_pending(&issuer, &alice, &VerificationType::Over18), 0);
+    assert_eq!(client.get_pending_points(&alice), 15);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::Over18), 15);
+    assert_eq!(client.reject_verification(&issuer, &alice, &VerificationType::Over18), 0);
+    assert_eq!(client.get_score(&alice), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_pending_points_not_registered_should_panic() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let _ = client.get_pending_points(&Address::generate(&env));
+}
+
+#[test]
+fn test_status_change_requires_scope_and_existing_verification() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    assert_eq!(
+        client.try_approve_verification(&issuer, &alice, &VerificationType::Over18),
+        Err(Ok(PassportError::VerificationNotFound.into()))
+    );
+
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+
+    let social_bot = Address::generate(&env);
+    client.add_issuer(&social_bot);
+    client.grant_scope(&social_bot, &VerificationType::Twitter);
+    assert_eq!(
+        client.try_approve_verification(&social_bot, &alice, &VerificationType::Over18),
+        Err(Ok(PassportError::ScopeNotGranted.into()))
+    );
+    assert_eq!(
+        client.try_approve_verification(&alice, &alice, &VerificationType::Over18),
+        Err(Ok(PassportError::IssuerNotRegistered.into()))
+    );
 }
 
 #[test]
 fn test_score_overflow_protection() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
     // Test with large but safe numbers
     let large_score = i32::MAX - 1000;
-    client.upsert_verification(&alice, &VerificationType::Over18, &large_score);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &large_score, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
     assert_eq!(client.get_score(&alice), large_score);
     
     // Add a small amount that should still be safe
-    client.upsert_verification(&alice, &VerificationType::Twitter, &500);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &500, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Twitter);
     assert_eq!(client.get_score(&alice), large_score + 500);
 }
 
@@ -329,41 +850,42 @@ fn test_score_overflow_protection() {
 fn test_score_overflow_should_panic() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
     // Set score close to max
     let near_max = i32::MAX - 100;
-    client.upsert_verification(&alice, &VerificationType::Over18, &near_max);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &near_max, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
     
-    // This should cause overflow
-    let _ = client.upsert_verification(&alice, &VerificationType::Twitter, &200);
+    // This should cause overflow once the second verification counts
+    let _ = client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &200, &None);
+    let _ = client.approve_verification(&issuer, &alice, &VerificationType::Twitter);
 }
 
 #[test]
 fn test_verification_count_tracking() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
 
     // Add multiple different verification types
     for i in 0..5 {
-        let sym = Symbol::new(&env, &format!("test{}", i));
-        client.upsert_verification(&alice, &VerificationType::Custom(sym), &10);
+        let vtype = VerificationType::Custom(Symbol::new(&env, &format!("test{}", i)));
+        client.grant_scope(&issuer, &vtype);
+        client.upsert_verification(&issuer, &alice, &vtype, &10, &None);
     }
     
     let verifs = client.get_verifications(&alice);
     assert_eq!(verifs.len(), 5);
     
     // Update an existing verification - count should not increase
-    client.upsert_verification(&alice, &VerificationType::Custom(Symbol::new(&env, "test0")), &20);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Custom(Symbol::new(&env, "test0")), &20, &None);
     
     let verifs = client.get_verifications(&alice);
     assert_eq!(verifs.len(), 5); // Count should remain the same
@@ -373,12 +895,12 @@ fn test_verification_count_tracking() {
 fn test_profile_update_preserves_score() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
-    client.upsert_verification(&alice, &VerificationType::Over18, &50);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &50, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
     
     assert_eq!(client.get_score(&alice), 50);
     
@@ -390,23 +912,2227 @@ fn test_profile_update_preserves_score() {
 }
 
 #[test]
-fn test_verification_timestamp_accuracy() {
+fn test_user_data_in_persistent_storage() {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register_contract(None, StellarPassport);
-    let client = StellarPassportClient::new(&env, &contract_id);
+    let (client, issuer) = setup(&env);
 
     let alice = Address::generate(&env);
     client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
-    
-    let before = env.ledger().timestamp();
-    client.upsert_verification(&alice, &VerificationType::Over18, &10);
-    let after = env.ledger().timestamp();
-    
-    let verifs = client.get_verifications(&alice);
-    let verif = &verifs.get(0).unwrap();
-    
-    // Timestamp should be within the expected range
-    assert!(verif.timestamp >= before);
-    assert!(verif.timestamp <= after);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+
+    let ttl = client.get_ttl_config();
+    env.as_contract(&client.address, || {
+        let user_key = DataKey::User(alice.clone());
+        let types_key = DataKey::VerificationTypes(alice.clone());
+        let verif_key = DataKey::Verification(alice.clone(), VerificationType::Over18);
+        for key in [&user_key, &types_key, &verif_key] {
+            assert!(env.storage().persistent().has(key));
+            assert!(!env.storage().instance().has(key));
+            assert_eq!(env.storage().persistent().get_ttl(key), ttl.extend_to);
+        }
+        let types: Vec<VerificationType> = env.storage().persistent().get(&types_key).unwrap();
+        assert_eq!(types, vec![&env, VerificationType::Over18]);
+        assert!(!env.storage().persistent().has(&DataKey::Verifications(alice.clone())));
+    });
+}
+
+#[test]
+fn test_bump_passport_extends_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    client.set_ttl_config(&5_000, &10_000);
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+
+    // Let the entries age past the threshold
+    env.ledger().with_mut(|li| li.sequence_number += 9_950);
+    let user_key = DataKey::User(alice.clone());
+    let types_key = DataKey::VerificationTypes(alice.clone());
+    let verif_key = DataKey::Verification(alice.clone(), VerificationType::Over18);
+    env.as_contract(&client.address, || {
+        assert!(env.storage().persistent().get_ttl(&user_key) < 5_000);
+    });
+
+    // Anyone can bump, no auth required
+    env.set_auths(&[]);
+    client.bump_passport(&alice);
+    env.as_contract(&client.address, || {
+        assert_eq!(env.storage().persistent().get_ttl(&user_key), 10_000);
+        assert_eq!(env.storage().persistent().get_ttl(&types_key), 10_000);
+        assert_eq!(env.storage().persistent().get_ttl(&verif_key), 10_000);
+    });
+
+    assert_eq!(
+        client.try_bump_passport(&Address::generate(&env)),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_ttl_config_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    client.set_ttl_config(&10, &20);
+    assert_eq!(client.get_ttl_config(), TtlConfig { threshold: 10, extend_to: 20 });
+
+    let invalid = Err(Ok(PassportError::InvalidConfig.into()));
+    assert_eq!(client.try_set_ttl_config(&20, &20), invalid);
+    assert_eq!(client.try_set_ttl_config(&30, &20), invalid);
+    assert_eq!(client.try_set_ttl_config(&10, &u32::MAX), invalid);
+}
+
+// Writes a passport the way the v1 layout stored it: in instance storage,
+// with a pending verification the wallet submitted itself, whose points the
+// score already added up.
+fn write_legacy_passport(env: &Env, client: &StellarPassportClient, wallet: &Address, points: i32) {
+    env.as_contract(&client.address, || {
+        let user = User {
+            wallet: wallet.clone(),
+            name: String::from_str(env, "Legacy"),
+            surnames: String::from_str(env, "User"),
+            score: points,
+            ver_count: 1,
+        };
+        let verifs: Vec<VerificationV2> = vec![
+            env,
+            VerificationV2 {
+                vtype: VerificationType::Over18,
+                points,
+                timestamp: env.ledger().timestamp(),
+                issuer: wallet.clone(),
+                status: Status::Pending,
+            },
+        ];
+        env.storage().instance().set(&DataKey::User(wallet.clone()), &user);
+        env.storage()
+            .instance()
+            .set(&DataKey::Verifications(wallet.clone()), &verifs);
+        env.storage().instance().set(&DataKey::Version, &1u32);
+    });
+}
+
+#[test]
+fn test_migrate_legacy_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    let wallets: [Address; 3] = core::array::from_fn(|_| Address::generate(&env));
+    for (i, wallet) in wallets.iter().enumerate() {
+        write_legacy_passport(&env, &client, wallet, 10 * (i as i32 + 1));
+    }
+    assert_eq!(client.schema_version(), 1);
+
+    // User data cannot be touched until the migration is done
+    assert_eq!(
+        client.try_get_score(&wallets[0]),
+        Err(Ok(PassportError::MigrationRequired.into()))
+    );
+    assert_eq!(
+        client.try_register(&wallets[0], &String::from_str(&env, "A"), &String::from_str(&env, "B")),
+        Err(Ok(PassportError::MigrationRequired.into()))
+    );
+
+    let mut to_index = Vec::new(&env);
+    for wallet in wallets.iter() {
+        to_index.push_back(wallet.clone());
+    }
+    to_index.push_back(Address::generate(&env)); // never registered, skipped
+    client.index_wallets(&to_index);
+
+    // Resumable in batches
+    env.ledger().set_sequence_number(100);
+    assert_eq!(client.migrate(&2), 1);
+    assert_eq!(client.schema_version(), 1);
+    assert_eq!(client.migrate(&2), 0);
+    assert_eq!(last_event(&env), Event::Migrated(1, SCHEMA_VERSION));
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    for (i, wallet) in wallets.iter().enumerate() {
+        // v1 scored pending points; only approved ones count now
+        assert_eq!(client.get_score(wallet), 0);
+        assert_eq!(client.get_raw_score(wallet), 0);
+        let verifs = client.get_verifications(wallet);
+        assert_eq!(verifs.len(), 1);
+        let verif = verifs.get(0).unwrap();
+        assert_eq!(verif.points, 10 * (i as i32 + 1));
+        assert_eq!(verif.status, Status::Pending);
+        assert_eq!(verif.issuer, *wallet);
+        env.as_contract(&client.address, || {
+            assert!(!env.storage().instance().has(&DataKey::User(wallet.clone())));
+            assert!(!env.storage().instance().has(&DataKey::Verifications(wallet.clone())));
+            assert!(env.storage().persistent().has(&DataKey::User(wallet.clone())));
+            assert!(env
+                .storage()
+                .persistent()
+                .has(&DataKey::Verification(wallet.clone(), VerificationType::Over18)));
+        });
+    }
+
+    // Score history starts at the migration
+    assert_eq!(client.get_score_at(&wallets[2], &100), 0);
+    assert_eq!(
+        client.try_get_score_at(&wallets[2], &99),
+        Err(Ok(PassportError::CheckpointUnavailable.into()))
+    );
+
+    // Migrated users keep working with the regular flows
+    client.upsert_verification(&issuer, &wallets[0], &VerificationType::Over18, &15, &None);
+    assert_eq!(client.get_score(&wallets[0]), 0);
+    assert_eq!(client.approve_verification(&issuer, &wallets[0], &VerificationType::Over18), 15);
+    assert_eq!(client.get_raw_score(&wallets[0]), 15);
+
+    // Nothing left to do
+    assert_eq!(client.migrate(&10), 0);
+}
+
+#[test]
+fn test_register_indexes_wallet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    // A legacy passport written later is migrated alongside the indexed one
+    let bob = Address::generate(&env);
+    write_legacy_passport(&env, &client, &bob, 5);
+    // Already-indexed wallets on the current layout are skipped
+    client.index_wallets(&vec![&env, bob.clone(), alice.clone()]);
+    env.as_contract(&client.address, || {
+        let count: u32 = env.storage().instance().get(&DataKey::WalletCount).unwrap();
+        assert_eq!(count, 2);
+        let first: Address = env.storage().persistent().get(&DataKey::Wallet(0)).unwrap();
+        assert_eq!(first, alice);
+    });
+
+    assert_eq!(client.migrate(&1), 1);
+    assert_eq!(client.migrate(&1), 0);
+    assert_eq!(client.get_score(&alice), 0);
+    assert_eq!(client.get_verifications(&bob).get(0).unwrap().points, 5);
+}
+
+#[test]
+fn test_migrate_wallet_indexed_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let bob = Address::generate(&env);
+    write_legacy_passport(&env, &client, &bob, 5);
+    client.index_wallets(&vec![&env, bob.clone()]);
+    client.index_wallets(&vec![&env, bob.clone()]);
+
+    assert_eq!(client.migrate(&10), 0);
+    assert_eq!(client.get_score(&bob), 0);
+    let verifs = client.get_verifications(&bob);
+    assert_eq!(verifs.len(), 1);
+    let verif = verifs.get(0).unwrap();
+    assert_eq!(verif.points, 5);
+    assert_eq!(verif.expires_at, None);
+    assert_eq!(verif.attesters, vec![&env, bob.clone()]);
+}
+
+#[test]
+fn test_index_wallets_after_migration() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    write_legacy_passport(&env, &client, &alice, 10);
+    write_legacy_passport(&env, &client, &bob, 5);
+    client.index_wallets(&vec![&env, alice.clone()]);
+    assert_eq!(client.migrate(&10), 0);
+    assert_eq!(client.schema_version(), SCHEMA_VERSION);
+
+    // Bob was missed: his v1 passport is still registered
+    assert_eq!(
+        client.try_register(&bob, &String::from_str(&env, "Bob"), &String::from_str(&env, "Doe")),
+        Err(Ok(PassportError::AlreadyRegistered.into()))
+    );
+
+    // Indexing him late migrates him right away
+    client.index_wallets(&vec![&env, bob.clone()]);
+    assert_eq!(client.get_raw_score(&bob), 0);
+    assert_eq!(client.get_verifications(&bob).len(), 1);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().instance().has(&DataKey::User(bob.clone())));
+        assert!(!env.storage().instance().has(&DataKey::Verifications(bob.clone())));
+        let count: u32 = env.storage().instance().get(&DataKey::WalletCount).unwrap();
+        assert_eq!(count, 2);
+    });
+    assert_eq!(client.migrate(&10), 0);
+}
+
+#[test]
+fn test_migrate_single_vec_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    // Schema 3 kept every verification in one `Vec`
+    env.as_contract(&client.address, || {
+        let verifs = vec![
+            &env,
+            VerificationV4 {
+                vtype: VerificationType::Over18,
+                points: 10,
+                timestamp: 0,
+                expires_at: None,
+                issuer: issuer.clone(),
+                status: Status::Approved,
+            },
+            VerificationV4 {
+                vtype: VerificationType::GitHub,
+                points: 5,
+                timestamp: 0,
+                expires_at: Some(5_000),
+                issuer: issuer.clone(),
+                status: Status::Pending,
+            },
+        ];
+        let mut user: User = env.storage().persistent().get(&DataKey::User(alice.clone())).unwrap();
+        user.score = 10;
+        user.ver_count = 2;
+        env.storage().persistent().set(&DataKey::User(alice.clone()), &user);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Verifications(alice.clone()), &verifs);
+        env.storage().instance().set(&DataKey::Version, &3u32);
+    });
+
+    assert_eq!(client.migrate(&10), 0);
+    assert_eq!(last_event(&env), Event::Migrated(3, SCHEMA_VERSION));
+    assert_eq!(client.get_score(&alice), 10);
+    let verifs = client.get_verifications(&alice);
+    assert_eq!(verifs.len(), 2);
+    assert_eq!(verifs.get(1).unwrap().expires_at, Some(5_000));
+    assert_eq!(verifs.get(1).unwrap().attesters, vec![&env, issuer.clone()]);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().persistent().has(&DataKey::Verifications(alice.clone())));
+    });
+
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(client.get_score(&alice), 15);
+}
+
+#[test]
+fn test_migrate_entries_without_attesters() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &5, &None);
+
+    // Schema 4 had one entry per type, without `attesters`
+    env.as_contract(&client.address, || {
+        let key = DataKey::Verification(alice.clone(), VerificationType::Over18);
+        let verif = VerificationV4 {
+            vtype: VerificationType::Over18,
+            points: 10,
+            timestamp: 0,
+            expires_at: None,
+            issuer: issuer.clone(),
+            status: Status::Approved,
+        };
+        env.storage().persistent().set(&key, &verif);
+        let types_key = DataKey::VerificationTypes(alice.clone());
+        let types = vec![&env, VerificationType::GitHub, VerificationType::Over18];
+        env.storage().persistent().set(&types_key, &types);
+        let mut user: User = env.storage().persistent().get(&DataKey::User(alice.clone())).unwrap();
+        user.score = 10;
+        user.ver_count = 2;
+        env.storage().persistent().set(&DataKey::User(alice.clone()), &user);
+        env.storage().instance().set(&DataKey::Version, &4u32);
+    });
+    assert_eq!(
+        client.try_get_verification(&alice, &VerificationType::Over18),
+        Err(Ok(PassportError::MigrationRequired.into()))
+    );
+
+    assert_eq!(client.migrate(&10), 0);
+    assert_eq!(last_event(&env), Event::Migrated(4, SCHEMA_VERSION));
+
+    let verif = client.get_verification(&alice, &VerificationType::Over18).unwrap();
+    assert_eq!(verif.attesters, vec![&env, issuer.clone()]);
+    assert_eq!(verif.points, 10);
+    // Entries already carrying attesters are kept as they are
+    let github = client.get_verification(&alice, &VerificationType::GitHub).unwrap();
+    assert_eq!(github.attesters, vec![&env, issuer.clone()]);
+    assert_eq!(client.get_score(&alice), 10);
+
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(client.get_score(&alice), 15);
+}
+
+#[test]
+fn test_migrate_adds_first_checkpoint() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    // Schema 5 kept no score history
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::CheckpointLen(alice.clone()));
+        storage.remove(&DataKey::Checkpoint(alice.clone(), 0));
+        storage.remove(&DataKey::Checkpoint(alice.clone(), 1));
+        env.storage().instance().set(&DataKey::Version, &5u32);
+    });
+
+    env.ledger().set_sequence_number(50);
+    assert_eq!(client.migrate(&10), 0);
+    assert_eq!(last_event(&env), Event::Migrated(5, SCHEMA_VERSION));
+    assert_eq!(client.get_score_at(&alice, &50), 10);
+    assert_eq!(
+        client.try_get_score_at(&alice, &49),
+        Err(Ok(PassportError::CheckpointUnavailable.into()))
+    );
+}
+
+#[test]
+fn test_migrate_first_checkpoint_is_effective_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &50, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.set_point_rule(&VerificationType::Over18, &PointRule::Fixed(10));
+
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::CheckpointLen(alice.clone()));
+        storage.remove(&DataKey::Checkpoint(alice.clone(), 0));
+        storage.remove(&DataKey::Checkpoint(alice.clone(), 1));
+        storage.remove(&DataKey::ScoreTotals(alice.clone()));
+        env.storage().instance().set(&DataKey::Version, &5u32);
+    });
+
+    env.ledger().set_sequence_number(50);
+    assert_eq!(client.migrate(&10), 0);
+    // The score under the point rule, not the raw points
+    assert_eq!(client.get_raw_score(&alice), 50);
+    assert_eq!(client.get_score_at(&alice, &50), 10);
+}
+
+#[test]
+fn test_migrate_builds_score_totals() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &30, &None);
+
+    // Schema 6 scored from the verification entries themselves
+    env.as_contract(&client.address, || {
+        env.storage().persistent().remove(&DataKey::ScoreTotals(alice.clone()));
+        env.storage().instance().set(&DataKey::Version, &6u32);
+    });
+
+    assert_eq!(client.migrate(&10), 0);
+    assert_eq!(last_event(&env), Event::Migrated(6, SCHEMA_VERSION));
+    env.as_contract(&client.address, || {
+        assert!(env.storage().persistent().has(&DataKey::ScoreTotals(alice.clone())));
+    });
+    // Only the approved verification is in the totals
+    assert_eq!(client.get_score(&alice), 10);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::GitHub), 40);
+}
+
+#[test]
+fn test_score_totals_follow_config_and_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &30, &Some(2_000));
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::GitHub), 40);
+
+    // A point rule conforms the points already in the totals
+    client.set_point_rule(&VerificationType::GitHub, &PointRule::Fixed(5));
+    assert_eq!(client.get_score(&alice), 15);
+    assert_eq!(
+        client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &7, &None),
+        15
+    );
+
+    // So do new categories
+    let policy = ScoringPolicy {
+        categories: map![&env, (VerificationType::GitHub, symbol_short!("over18"))],
+        caps: map![&env, (symbol_short!("over18"), 12)],
+        ..client.get_scoring_policy()
+    };
+    client.set_scoring_policy(&policy);
+    assert_eq!(client.get_score(&alice), 12);
+    assert_eq!(
+        client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &7, &None),
+        12
+    );
+
+    // And an expiry
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.get_score(&alice), 10);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::Twitter), 17);
+}
+
+#[test]
+fn test_migrate_rejects_zero_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    assert_eq!(
+        client.try_migrate(&0),
+        Err(Ok(PassportError::InvalidBatchSize.into()))
+    );
+}
+
+#[test]
+fn test_expired_verifications_do_not_count() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &Some(2_000));
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &5, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::GitHub), 15);
+
+    env.ledger().set_timestamp(1_999);
+    assert_eq!(client.get_score(&alice), 15);
+
+    // Expiry is inclusive
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.get_score(&alice), 5);
+
+    // Expired verifications stay listed
+    assert_eq!(client.get_verifications(&alice).len(), 2);
+
+    // Pending points also ignore expired verifications
+    client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &7, &Some(3_000));
+    assert_eq!(client.get_pending_points(&alice), 7);
+    env.ledger().set_timestamp(3_000);
+    assert_eq!(client.get_pending_points(&alice), 0);
+}
+
+#[test]
+fn test_default_validity_per_type() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    client.set_default_validity(&VerificationType::Over18, &500);
+    assert_eq!(client.get_default_validity(&VerificationType::Over18), Some(500));
+    assert_eq!(client.get_default_validity(&VerificationType::GitHub), None);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &10, &None);
+
+    let verifs = client.get_verifications(&alice);
+    assert_eq!(verifs.get(0).unwrap().expires_at, Some(1_500));
+    assert_eq!(verifs.get(1).unwrap().expires_at, None);
+
+    // An explicit expiry wins over the default
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &Some(5_000));
+    assert_eq!(client.get_verifications(&alice).get(0).unwrap().expires_at, Some(5_000));
+
+    client.set_default_validity(&VerificationType::Over18, &0);
+    assert_eq!(client.get_default_validity(&VerificationType::Over18), None);
+}
+
+#[test]
+fn test_expiry_must_be_in_the_future() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert_eq!(
+        client.try_upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &Some(1_000)),
+        Err(Ok(PassportError::InvalidExpiry.into()))
+    );
+}
+
+#[test]
+fn test_point_table_enforced() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let kyc = VerificationType::Custom(symbol_short!("kyc"));
+    client.grant_scope(&issuer, &kyc);
+
+    client.set_point_rule(&VerificationType::Over18, &PointRule::Fixed(10));
+    assert_eq!(
+        last_event(&env),
+        Event::PointRuleSet(VerificationType::Over18, PointRule::Fixed(10))
+    );
+    client.set_point_rule(&kyc, &PointRule::Range(20, 40));
+    let table = client.get_point_table();
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.get(kyc.clone()), Some(PointRule::Range(20, 40)));
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    for (vtype, points) in [
+        (VerificationType::Over18, 9),
+        (VerificationType::Over18, 11),
+        (kyc.clone(), 19),
+        (kyc.clone(), 41),
+        (VerificationType::GitHub, 0),
+    ] {
+        assert_eq!(
+            client.try_upsert_verification(&issuer, &alice, &vtype, &points, &None),
+            Err(Ok(PassportError::InvalidPoints.into()))
+        );
+    }
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.upsert_verification(&issuer, &alice, &kyc, &20, &None);
+    client.upsert_verification(&issuer, &alice, &kyc, &40, &None);
+    // Types without a rule accept any positive value
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &1_000, &None);
+
+    // Other custom symbols are not covered by the rule
+    let other = VerificationType::Custom(symbol_short!("other"));
+    client.grant_scope(&issuer, &other);
+    client.upsert_verification(&issuer, &alice, &other, &1, &None);
+
+    client.remove_point_rule(&VerificationType::Over18);
+    assert_eq!(last_event(&env), Event::PointRuleRemoved(VerificationType::Over18));
+    assert_eq!(client.get_point_table().len(), 1);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &11, &None);
+}
+
+#[test]
+fn test_point_rule_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    for rule in [
+        PointRule::Fixed(0),
+        PointRule::Fixed(-5),
+        PointRule::Range(0, 10),
+        PointRule::Range(10, 5),
+    ] {
+        assert_eq!(
+            client.try_set_point_rule(&VerificationType::Over18, &rule),
+            Err(Ok(PassportError::InvalidConfig.into()))
+        );
+    }
+    client.set_point_rule(&VerificationType::Over18, &PointRule::Range(5, 5));
+}
+
+#[test]
+fn test_recompute_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &50, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &5, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &30, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert_eq!(client.get_score(&alice), 55);
+
+    // Scores count existing verifications as the new table allows
+    env.ledger().set_sequence_number(10);
+    client.set_point_rule(&VerificationType::Over18, &PointRule::Fixed(10));
+    client.set_point_rule(&VerificationType::GitHub, &PointRule::Range(8, 20));
+    client.set_point_rule(&VerificationType::Twitter, &PointRule::Range(1, 20));
+    assert_eq!(client.get_score(&alice), 18);
+    // Pending ones too
+    assert_eq!(client.get_pending_points(&alice), 20);
+
+    // Recomputing records the change, without rewriting what issuers stored
+    assert_eq!(client.recompute_score(&alice), 18);
+    assert_eq!(last_event(&env), Event::ScoreRecomputed(alice.clone(), 55, 18));
+    assert_eq!(client.get_score_at(&alice, &10), 18);
+    let verifs = client.get_verifications(&alice);
+    assert_eq!(verifs.get(0).unwrap().points, 50);
+    assert_eq!(verifs.get(1).unwrap().points, 5);
+    assert_eq!(verifs.get(2).unwrap().points, 30);
+    assert_eq!(client.get_raw_score(&alice), 55);
+
+    // Later transitions stay consistent, and loosening the table restores
+    // the issuers' points
+    assert_eq!(client.reject_verification(&issuer, &alice, &VerificationType::Over18), 8);
+    client.remove_point_rule(&VerificationType::GitHub);
+    assert_eq!(client.get_score(&alice), 5);
+
+    assert_eq!(
+        client.try_recompute_score(&Address::generate(&env)),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_scoring_policy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    let mut vtypes = vec![&env, VerificationType::Over18, VerificationType::GitHub];
+    for name in ["kyc_a", "kyc_b", "kyc_c"] {
+        let custom = VerificationType::Custom(Symbol::new(&env, name));
+        client.grant_scope(&issuer, &custom);
+        vtypes.push_back(custom);
+    }
+    for (vtype, points) in vtypes.iter().zip([50, 10, 20, 20, 20]) {
+        client.upsert_verification(&issuer, &alice, &vtype, &points, &None);
+        client.approve_verification(&issuer, &alice, &vtype);
+    }
+
+    // The default policy is the plain sum
+    let default = client.get_scoring_policy();
+    assert_eq!(default.decay_bps, 10_000);
+    assert_eq!(client.get_score(&alice), 120);
+    assert_eq!(client.preview_score(&alice, &default), 120);
+
+    // Over18 doubled, custom ones halved each time and capped at 30
+    let policy = ScoringPolicy {
+        categories: map![&env, (VerificationType::Over18, symbol_short!("id"))],
+        weights: map![&env, (symbol_short!("id"), 20_000)],
+        caps: map![&env, (symbol_short!("custom"), 30)],
+        decay_bps: 5_000,
+    };
+    assert_eq!(client.preview_score(&alice, &policy), 100 + 10 + 30);
+    assert_eq!(client.get_score(&alice), 120);
+
+    client.set_scoring_policy(&policy);
+    assert_eq!(last_event(&env), Event::ScoringPolicySet(policy.clone()));
+    assert_eq!(client.get_scoring_policy(), policy);
+    assert_eq!(client.get_score(&alice), 140);
+
+    // Returned scores follow the policy too
+    assert_eq!(client.reject_verification(&issuer, &alice, &VerificationType::GitHub), 130);
+
+    // Diminishing returns apply from the highest contribution down
+    let uncapped = ScoringPolicy {
+        caps: Map::new(&env),
+        ..policy.clone()
+    };
+    let kyc_a = VerificationType::Custom(symbol_short!("kyc_a"));
+    client.upsert_verification(&issuer, &alice, &kyc_a, &40, &None);
+    assert_eq!(client.preview_score(&alice, &uncapped), 100 + 40 + 10 + 5);
+}
+
+#[test]
+fn test_scoring_policy_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let increasing = ScoringPolicy {
+        decay_bps: 10_001,
+        ..client.get_scoring_policy()
+    };
+    assert_eq!(
+        client.try_set_scoring_policy(&increasing),
+        Err(Ok(PassportError::InvalidConfig.into()))
+    );
+
+    let negative_cap = ScoringPolicy {
+        caps: map![&env, (symbol_short!("custom"), -1)],
+        ..client.get_scoring_policy()
+    };
+    assert_eq!(
+        client.try_set_scoring_policy(&negative_cap),
+        Err(Ok(PassportError::InvalidConfig.into()))
+    );
+
+    // Weights above 10x could overflow the score
+    for weight in [100_001, u32::MAX] {
+        let heavy = ScoringPolicy {
+            weights: map![&env, (symbol_short!("custom"), weight)],
+            ..client.get_scoring_policy()
+        };
+        assert_eq!(
+            client.try_set_scoring_policy(&heavy),
+            Err(Ok(PassportError::InvalidConfig.into()))
+        );
+    }
+    client.set_scoring_policy(&ScoringPolicy {
+        weights: map![&env, (symbol_short!("custom"), 100_000)],
+        ..client.get_scoring_policy()
+    });
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert_eq!(
+        client.try_preview_score(&alice, &negative_cap),
+        Err(Ok(PassportError::InvalidConfig.into()))
+    );
+}
+
+#[test]
+fn test_score_decay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &1_000, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &100, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+
+    const DAY: u64 = 86_400;
+    client.set_half_life(&VerificationType::Over18, &(30 * DAY));
+    assert_eq!(last_event(&env), Event::HalfLifeSet(VerificationType::Over18, 30 * DAY));
+    assert_eq!(client.get_half_lives().get(VerificationType::Over18), Some(30 * DAY));
+    assert_eq!(client.get_score(&alice), 1_100);
+
+    // Over18 halves every 30 days, GitHub does not decay
+    for (days, over18) in [(15, 707), (30, 500), (45, 353), (60, 250), (90, 125), (300, 0)] {
+        env.ledger().set_timestamp(1_000 + days * DAY);
+        assert_eq!(client.get_score(&alice), over18 + 100, "after {} days", days);
+    }
+    assert_eq!(client.get_raw_score(&alice), 1_100);
+
+    // Renewing restarts the decay
+    env.ledger().set_timestamp(1_000 + 60 * DAY);
+    assert_eq!(
+        client.renew_verification(&issuer, &alice, &VerificationType::Over18, &None),
+        1_100
+    );
+
+    client.set_half_life(&VerificationType::Over18, &0);
+    assert_eq!(client.get_half_lives().len(), 0);
+    env.ledger().set_timestamp(1_000 + 600 * DAY);
+    assert_eq!(client.get_score(&alice), 1_100);
+}
+
+#[test]
+fn test_decay_curve() {
+    use crate::scoring::decayed_points;
+
+    let half_life = 1_000u64;
+    let mut previous = 10_000;
+    for age in (0..=20_000u64).step_by(7) {
+        let points = decayed_points(10_000, 0, age, half_life);
+        assert!(points <= previous, "not decreasing at {}", age);
+        previous = points;
+    }
+
+    // Exact at every half-life and at table points
+    assert_eq!(decayed_points(10_000, 0, 0, half_life), 10_000);
+    assert_eq!(decayed_points(10_000, 0, 1_000, half_life), 5_000);
+    assert_eq!(decayed_points(10_000, 0, 3_000, half_life), 1_250);
+    assert_eq!(decayed_points(10_000, 0, 250, half_life), 8_408); // 2^-0.25 = 0.84089
+    assert_eq!(decayed_points(10_000, 0, 500, half_life), 7_071); // 2^-0.5 = 0.70710
+    // Interpolated in between: 2^-0.3 = 0.81225
+    assert_eq!(decayed_points(10_000, 0, 300, half_life), 8_123);
+    // Half-lives too long for the interpolation are scaled down
+    assert_eq!(decayed_points(10_000, 0, u64::MAX / 2, u64::MAX), 7_071);
+    assert_eq!(decayed_points(10_000, 0, u64::MAX / 4, u64::MAX), 8_408);
+    // Out of range ages
+    assert_eq!(decayed_points(i32::MAX, 0, 64 * half_life, half_life), 0);
+    assert_eq!(decayed_points(10_000, 500, 0, half_life), 10_000);
+}
+
+fn tier(env: &Env, name: &str, min_score: i32) -> Tier {
+    Tier {
+        name: Symbol::new(env, name),
+        min_score,
+    }
+}
+
+#[test]
+fn test_tiers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    let none = symbol_short!("none");
+    assert_eq!(client.get_tier(&alice), none);
+
+    let tiers = vec![&env, tier(&env, "bronze", 10), tier(&env, "silver", 50), tier(&env, "gold", 100)];
+    client.set_tiers(&tiers);
+    assert_eq!(last_event(&env), Event::TiersSet(tiers.clone()));
+    assert_eq!(client.get_tiers(), tiers);
+
+    // Pending points do not move the tier
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &60, &None);
+    assert!(matches!(last_event(&env), Event::VerificationUpserted(..)));
+    assert_eq!(client.get_tier(&alice), none);
+
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    assert_eq!(
+        last_event(&env),
+        Event::TierChanged(alice.clone(), none.clone(), symbol_short!("silver"))
+    );
+    assert_eq!(client.get_tier(&alice), symbol_short!("silver"));
+
+    // Moving within a tier emits nothing
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &5, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert!(matches!(last_event(&env), Event::VerificationStatusChanged(..)));
+
+    // Thresholds are inclusive
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &95, &None);
+    assert_eq!(
+        last_event(&env),
+        Event::TierChanged(alice.clone(), symbol_short!("silver"), symbol_short!("gold"))
+    );
+
+    client.revoke_verification(&issuer, &alice, &VerificationType::Over18, &symbol_short!("fraud"));
+    assert_eq!(
+        last_event(&env),
+        Event::TierChanged(alice.clone(), symbol_short!("gold"), none.clone())
+    );
+
+    client.set_tiers(&Vec::new(&env));
+    assert_eq!(client.get_tiers().len(), 0);
+}
+
+#[test]
+fn test_tiers_must_increase() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    for tiers in [
+        vec![&env, tier(&env, "bronze", 10), tier(&env, "silver", 10)],
+        vec![&env, tier(&env, "silver", 50), tier(&env, "bronze", 10)],
+        vec![&env, tier(&env, "none", 0)],
+    ] {
+        assert_eq!(
+            client.try_set_tiers(&tiers),
+            Err(Ok(PassportError::InvalidConfig.into()))
+        );
+    }
+}
+
+// Alice: Over18 approved (expires at 2_000), GitHub pending, Twitter rejected,
+// WorldID approved then revoked. Score 10.
+fn setup_requirements(env: &Env) -> (StellarPassportClient<'_>, Address, Address) {
+    let (client, issuer) = setup(env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(env);
+    client.register(&alice, &String::from_str(env, "Alice"), &String::from_str(env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &Some(2_000));
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &20, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &5, &None);
+    client.reject_verification(&issuer, &alice, &VerificationType::Twitter);
+    client.upsert_verification(&issuer, &alice, &VerificationType::WorldID, &40, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::WorldID);
+    client.revoke_verification(&issuer, &alice, &VerificationType::WorldID, &symbol_short!("fraud"));
+    (client, issuer, alice)
+}
+
+#[test]
+fn test_requirement_leaves() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer, alice) = setup_requirements(&env);
+    let meets = |req: Requirement| client.meets_requirements(&alice, &req);
+
+    assert!(meets(Requirement::HasType(VerificationType::GitHub)));
+    assert!(meets(Requirement::HasType(VerificationType::Twitter)));
+    assert!(!meets(Requirement::HasType(VerificationType::BrightID)));
+    assert!(!meets(Requirement::HasType(VerificationType::WorldID)));
+
+    assert!(meets(Requirement::Approved(VerificationType::Over18)));
+    assert!(!meets(Requirement::Approved(VerificationType::GitHub)));
+    assert!(!meets(Requirement::Approved(VerificationType::WorldID)));
+
+    assert!(meets(Requirement::IssuedBy(VerificationType::GitHub, issuer.clone())));
+    assert!(!meets(Requirement::IssuedBy(VerificationType::GitHub, Address::generate(&env))));
+
+    assert!(meets(Requirement::MinScore(10)));
+    assert!(!meets(Requirement::MinScore(11)));
+
+    assert!(meets(Requirement::NotExpired(VerificationType::Over18)));
+    env.ledger().set_timestamp(2_000);
+    assert!(!meets(Requirement::NotExpired(VerificationType::Over18)));
+    assert!(!meets(Requirement::Approved(VerificationType::Over18)));
+    assert!(meets(Requirement::NotExpired(VerificationType::GitHub)));
+    assert!(!meets(Requirement::MinScore(1)));
+}
+
+#[test]
+fn test_requirement_operators() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, alice) = setup_requirements(&env);
+    let meets = |req: Requirement| client.meets_requirements(&alice, &req);
+    let yes = || Requirement::Approved(VerificationType::Over18);
+    let no = || Requirement::Approved(VerificationType::GitHub);
+
+    assert!(meets(Requirement::And(vec![&env, yes(), yes()])));
+    assert!(!meets(Requirement::And(vec![&env, yes(), no()])));
+    assert!(meets(Requirement::Or(vec![&env, no(), yes()])));
+    assert!(!meets(Requirement::Or(vec![&env, no(), no()])));
+    assert!(meets(Requirement::Not(vec![&env, no()])));
+    assert!(!meets(Requirement::Not(vec![&env, yes()])));
+
+    // has Over18 AND (GitHub OR Twitter) AND score >= 10
+    let gate = Requirement::And(vec![
+        &env,
+        Requirement::Approved(VerificationType::Over18),
+        Requirement::Or(vec![
+            &env,
+            Requirement::HasType(VerificationType::GitHub),
+            Requirement::HasType(VerificationType::Twitter),
+        ]),
+        Requirement::MinScore(10),
+    ]);
+    assert!(meets(gate.clone()));
+    env.ledger().set_timestamp(2_000);
+    assert!(!meets(gate));
+
+    assert_eq!(
+        client.try_meets_requirements(&Address::generate(&env), &yes()),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_requirement_bounds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _, alice) = setup_requirements(&env);
+    let leaf = || Requirement::MinScore(0);
+    let invalid = Err(Ok(PassportError::InvalidRequirement.into()));
+
+    // Depth: up to 5 levels
+    let mut nested = leaf();
+    for _ in 0..4 {
+        nested = Requirement::Not(vec![&env, nested]);
+    }
+    assert!(client.meets_requirements(&alice, &nested));
+    let too_deep = Requirement::Not(vec![&env, nested]);
+    assert_eq!(client.try_meets_requirements(&alice, &too_deep), invalid);
+
+    // Nodes: up to 32, checked even where evaluation would short-circuit
+    let mut wide = Vec::new(&env);
+    for _ in 0..31 {
+        wide.push_back(leaf());
+    }
+    assert!(client.meets_requirements(&alice, &Requirement::Or(wide.clone())));
+    wide.push_back(leaf());
+    assert_eq!(client.try_meets_requirements(&alice, &Requirement::Or(wide)), invalid);
+
+    // Shape
+    for req in [
+        Requirement::And(Vec::new(&env)),
+        Requirement::Or(Vec::new(&env)),
+        Requirement::Not(Vec::new(&env)),
+        Requirement::Not(vec![&env, leaf(), leaf()]),
+    ] {
+        assert_eq!(client.try_meets_requirements(&alice, &req), invalid);
+    }
+}
+
+#[test]
+fn test_flags_subtract_from_score() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    let sybil = symbol_short!("sybil");
+    assert_eq!(client.flag_wallet(&issuer, &alice, &sybil, &25), -15);
+    assert_eq!(
+        last_event(&env),
+        Event::WalletFlagged(alice.clone(), issuer.clone(), sybil.clone(), 25)
+    );
+    assert_eq!(client.get_score(&alice), -15);
+    assert_eq!(client.get_raw_score(&alice), -15);
+    assert_eq!(
+        client.get_flags(&alice),
+        vec![
+            &env,
+            Flag {
+                issuer: issuer.clone(),
+                reason: sybil.clone(),
+                penalty: 25,
+                timestamp: 1_000,
+            }
+        ]
+    );
+    // Flags are not verifications
+    assert_eq!(client.get_verifications(&alice).len(), 1);
+
+    // Flagging again with the same reason replaces the penalty
+    assert_eq!(client.flag_wallet(&issuer, &alice, &sybil, &5), 5);
+    assert_eq!(client.flag_wallet(&issuer, &alice, &symbol_short!("fraud"), &3), 2);
+    assert_eq!(client.get_flags(&alice).len(), 2);
+
+    // Verification writes keep the penalties
+    assert_eq!(client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &20, &None), 12);
+    assert_eq!(client.recompute_score(&alice), 12);
+    assert_eq!(client.get_raw_score(&alice), 12);
+
+    assert_eq!(
+        client.try_flag_wallet(&issuer, &alice, &sybil, &0),
+        Err(Ok(PassportError::InvalidPoints.into()))
+    );
+    assert_eq!(
+        client.try_flag_wallet(&Address::generate(&env), &alice, &sybil, &5),
+        Err(Ok(PassportError::IssuerNotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_lift_flag() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let admin = client.get_admin();
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    let sybil = symbol_short!("sybil");
+    let fraud = symbol_short!("fraud");
+    client.flag_wallet(&issuer, &alice, &sybil, &10);
+    client.flag_wallet(&issuer, &alice, &fraud, &5);
+
+    // Only the flag's issuer or the admin
+    let other = Address::generate(&env);
+    client.add_issuer(&other);
+    assert_eq!(
+        client.try_lift_flag(&other, &alice, &issuer, &sybil),
+        Err(Ok(PassportError::Unauthorized.into()))
+    );
+
+    assert_eq!(client.lift_flag(&issuer, &alice, &issuer, &sybil), -5);
+    assert_eq!(
+        last_event(&env),
+        Event::FlagLifted(alice.clone(), issuer.clone(), sybil.clone(), issuer.clone())
+    );
+    assert_eq!(client.lift_flag(&admin, &alice, &issuer, &fraud), 0);
+    assert_eq!(client.get_flags(&alice).len(), 0);
+    assert_eq!(client.get_raw_score(&alice), 0);
+
+    assert_eq!(
+        client.try_lift_flag(&issuer, &alice, &issuer, &sybil),
+        Err(Ok(PassportError::FlagNotFound.into()))
+    );
+}
+
+#[test]
+fn test_flags_limit_and_gating() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert!(client.meets_requirements(&alice, &Requirement::NoActiveFlags));
+
+    for i in 0..20 {
+        client.flag_wallet(&issuer, &alice, &Symbol::new(&env, &format!("r{}", i)), &1);
+    }
+    assert_eq!(
+        client.try_flag_wallet(&issuer, &alice, &symbol_short!("r20"), &1),
+        Err(Ok(PassportError::TooManyFlags.into()))
+    );
+    assert!(!client.meets_requirements(&alice, &Requirement::NoActiveFlags));
+    assert!(client.meets_requirements(&alice, &Requirement::MinScore(-20)));
+    assert!(!client.meets_requirements(&alice, &Requirement::MinScore(-19)));
+}
+
+#[test]
+fn test_revoke_verification() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &5, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    assert!(!client.is_revoked(&alice, &VerificationType::Over18));
+
+    let reason = symbol_short!("fraud");
+    assert_eq!(
+        client.revoke_verification(&issuer, &alice, &VerificationType::Over18, &reason),
+        5
+    );
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationRevoked(alice.clone(), VerificationType::Over18, issuer.clone(), reason.clone())
+    );
+    assert_eq!(client.get_score(&alice), 5);
+    assert!(client.is_revoked(&alice, &VerificationType::Over18));
+    assert!(!client.is_revoked(&alice, &VerificationType::GitHub));
+    assert_eq!(
+        client.get_revocation(&alice, &VerificationType::Over18),
+        Some(Revocation {
+            issuer: issuer.clone(),
+            reason: reason.clone(),
+            timestamp: 1_000,
+        })
+    );
+
+    // The record is kept, marked as revoked
+    let verifs = client.get_verifications(&alice);
+    assert_eq!(verifs.len(), 2);
+    assert_eq!(verifs.get(0).unwrap().status, Status::Revoked);
+
+    // Revoked is final for reviews and renewals
+    assert_eq!(
+        client.try_approve_verification(&issuer, &alice, &VerificationType::Over18),
+        Err(Ok(PassportError::InvalidStatusTransition.into()))
+    );
+    assert_eq!(
+        client.try_renew_verification(&issuer, &alice, &VerificationType::Over18, &None),
+        Err(Ok(PassportError::InvalidStatusTransition.into()))
+    );
+    assert_eq!(
+        client.try_revoke_verification(&issuer, &alice, &VerificationType::Over18, &reason),
+        Err(Ok(PassportError::InvalidStatusTransition.into()))
+    );
+
+    // Re-issuing starts a new review and clears the revocation
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    assert!(!client.is_revoked(&alice, &VerificationType::Over18));
+    assert_eq!(client.get_verifications(&alice).get(0).unwrap().status, Status::Pending);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::Over18), 15);
+}
+
+#[test]
+fn test_revoke_only_by_attesters() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let other = Address::generate(&env);
+    client.add_issuer(&other);
+    client.grant_scope(&other, &VerificationType::Over18);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    let reason = symbol_short!("hacked");
+    assert_eq!(
+        client.try_revoke_verification(&other, &alice, &VerificationType::Over18, &reason),
+        Err(Ok(PassportError::Unauthorized.into()))
+    );
+    assert_eq!(
+        client.try_revoke_verification(&issuer, &alice, &VerificationType::GitHub, &reason),
+        Err(Ok(PassportError::VerificationNotFound.into()))
+    );
+
+    // A renewal by another issuer does not take it away from the first one
+    client.renew_verification(&other, &alice, &VerificationType::Over18, &None);
+    assert_eq!(client.get_verification(&alice, &VerificationType::Over18).unwrap().issuer, other);
+
+    // Still allowed once the issuer is out of the registry
+    client.remove_issuer(&issuer);
+    assert_eq!(
+        client.revoke_verification(&issuer, &alice, &VerificationType::Over18, &reason),
+        0
+    );
+}
+
+fn check_submit_attestation(signer: TestSigner) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let key = signer.register(&env, &client, &issuer);
+    assert_eq!(last_event(&env), Event::IssuerKeyAdded(issuer.clone(), key.clone()));
+    assert_eq!(key, signer.key_id(&env));
+    assert_eq!(client.issuer_keys(&issuer), vec![&env, key.clone()]);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    let mut attestation = Attestation {
+        contract: client.address.clone(),
+        wallet: alice.clone(),
+        vtype: VerificationType::Over18,
+        points: 10,
+        expires_at: Some(5_000),
+        nonce: 0,
+    };
+    let (payload, signature) = sign_attestation(&env, &signer, &attestation);
+    assert_eq!(client.submit_attestation(&alice, &payload, &signature, &key), 0);
+    assert!(client.is_nonce_used(&issuer, &0));
+
+    // Applied on behalf of the key's issuer, like `upsert_verification`
+    let verif = client.get_verifications(&alice).get(0).unwrap();
+    assert_eq!(verif.issuer, issuer);
+    assert_eq!(verif.points, 10);
+    assert_eq!(verif.expires_at, Some(5_000));
+    assert_eq!(verif.status, Status::Pending);
+    assert_eq!(client.approve_verification(&issuer, &alice, &VerificationType::Over18), 10);
+
+    // Replays are rejected
+    assert_eq!(
+        client.try_submit_attestation(&alice, &payload, &signature, &key),
+        Err(Ok(PassportError::InvalidNonce.into()))
+    );
+
+    // Nonces can be used in any order, so one attestation left unsubmitted
+    // does not hold back the others
+    attestation.nonce = 70;
+    attestation.points = 15;
+    let (late_payload, late_signature) = sign_attestation(&env, &signer, &attestation);
+    attestation.nonce = 1;
+    attestation.points = 20;
+    let (payload, signature) = sign_attestation(&env, &signer, &attestation);
+    assert_eq!(client.submit_attestation(&alice, &payload, &signature, &key), 20);
+    assert_eq!(client.submit_attestation(&alice, &late_payload, &late_signature, &key), 15);
+    assert!(client.is_nonce_used(&issuer, &70));
+    assert!(!client.is_nonce_used(&issuer, &2));
+    assert_eq!(
+        client.try_submit_attestation(&alice, &late_payload, &late_signature, &key),
+        Err(Ok(PassportError::InvalidNonce.into()))
+    );
+
+    // They belong to the issuer, not to one of its keys
+    let second = match signer.algorithm() {
+        KeyAlgorithm::Ed25519 => TestSigner::ed25519(2),
+        KeyAlgorithm::Secp256r1 => TestSigner::secp256r1(2),
+    };
+    let second_key = second.register(&env, &client, &issuer);
+    let (payload, signature) = sign_attestation(&env, &second, &attestation);
+    assert_eq!(
+        client.try_submit_attestation(&alice, &payload, &signature, &second_key),
+        Err(Ok(PassportError::InvalidNonce.into()))
+    );
+}
+
+#[test]
+fn test_submit_attestation_ed25519() {
+    check_submit_attestation(TestSigner::ed25519(1));
+}
+
+#[test]
+fn test_submit_attestation_secp256r1() {
+    check_submit_attestation(TestSigner::secp256r1(1));
+}
+
+fn check_submit_attestation_rejects_invalid(signer: TestSigner, stranger: TestSigner) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let key = signer.register(&env, &client, &issuer);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.register(&bob, &String::from_str(&env, "Bob"), &String::from_str(&env, "Doe"));
+
+    let attestation = Attestation {
+        contract: client.address.clone(),
+        wallet: alice.clone(),
+        vtype: VerificationType::Over18,
+        points: 10,
+        expires_at: None,
+        nonce: 0,
+    };
+    let (payload, signature) = sign_attestation(&env, &signer, &attestation);
+
+    // Signed for another wallet
+    assert_eq!(
+        client.try_submit_attestation(&bob, &payload, &signature, &key),
+        Err(Ok(PassportError::InvalidAttestation.into()))
+    );
+
+    // Signed for another deployment
+    let other = Attestation {
+        contract: Address::generate(&env),
+        ..attestation.clone()
+    };
+    let (other_payload, other_signature) = sign_attestation(&env, &signer, &other);
+    assert_eq!(
+        client.try_submit_attestation(&alice, &other_payload, &other_signature, &key),
+        Err(Ok(PassportError::InvalidAttestation.into()))
+    );
+
+    // Unregistered key
+    let (payload_s, signature_s) = sign_attestation(&env, &stranger, &attestation);
+    assert_eq!(
+        client.try_submit_attestation(&alice, &payload_s, &signature_s, &stranger.key_id(&env)),
+        Err(Ok(PassportError::UnknownIssuerKey.into()))
+    );
+
+    // Signature from another key, or over another payload
+    assert!(client.try_submit_attestation(&alice, &payload, &signature_s, &key).is_err());
+    assert!(client.try_submit_attestation(&alice, &other_payload, &signature, &key).is_err());
+
+    // Payload that is not an attestation
+    let wrong_type = VerificationType::Over18.to_xdr(&env);
+    let wrong_type_signature = signer.sign(&env, &wrong_type);
+    assert_eq!(
+        client.try_submit_attestation(&alice, &wrong_type, &wrong_type_signature, &key),
+        Err(Ok(PassportError::InvalidAttestation.into()))
+    );
+    let garbage = Bytes::from_array(&env, &[1, 2, 3]);
+    let garbage_signature = signer.sign(&env, &garbage);
+    assert!(client.try_submit_attestation(&alice, &garbage, &garbage_signature, &key).is_err());
+
+    // Scopes of the key's issuer apply
+    let custom = VerificationType::Custom(symbol_short!("kyc"));
+    let scoped = Attestation {
+        vtype: custom,
+        ..attestation.clone()
+    };
+    let (payload, signature) = sign_attestation(&env, &signer, &scoped);
+    assert_eq!(
+        client.try_submit_attestation(&alice, &payload, &signature, &key),
+        Err(Ok(PassportError::ScopeNotGranted.into()))
+    );
+    assert!(!client.is_nonce_used(&issuer, &0));
+}
+
+#[test]
+fn test_submit_attestation_rejects_invalid() {
+    check_submit_attestation_rejects_invalid(TestSigner::ed25519(1), TestSigner::ed25519(2));
+    check_submit_attestation_rejects_invalid(TestSigner::secp256r1(1), TestSigner::secp256r1(2));
+    // Stranger on the other curve
+    check_submit_attestation_rejects_invalid(TestSigner::ed25519(1), TestSigner::secp256r1(2));
+}
+
+#[test]
+fn test_submit_passkey_attestation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let signer = TestSigner::secp256r1(1);
+    let key = signer.register(&env, &client, &issuer);
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    let mut attestation = Attestation {
+        contract: client.address.clone(),
+        wallet: alice.clone(),
+        vtype: VerificationType::Over18,
+        points: 10,
+        expires_at: None,
+        nonce: 0,
+    };
+    let payload = attestation.clone().to_xdr(&env);
+    // User present and verified
+    let (auth, client_data, signature) = passkey_assertion(&env, &signer, &payload, "webauthn.get", 0x05);
+    assert_eq!(
+        client.submit_passkey_attestation(&alice, &payload, &auth, &client_data, &signature, &key),
+        0
+    );
+    assert!(client.is_nonce_used(&issuer, &0));
+    let verif = client.get_verifications(&alice).get(0).unwrap();
+    assert_eq!(verif.issuer, issuer);
+    assert_eq!(verif.points, 10);
+
+    // Nonces are shared with `submit_attestation`
+    assert_eq!(
+        client.try_submit_passkey_attestation(&alice, &payload, &auth, &client_data, &signature, &key),
+        Err(Ok(PassportError::InvalidNonce.into()))
+    );
+
+    attestation.nonce = 1;
+    attestation.points = 15;
+    let payload = attestation.clone().to_xdr(&env);
+    let invalid = Err(Ok(PassportError::InvalidAttestation.into()));
+
+    // Challenge for another payload
+    let other = Attestation { nonce: 2, ..attestation.clone() }.to_xdr(&env);
+    let (a, c, sig) = passkey_assertion(&env, &signer, &other, "webauthn.get", 0x05);
+    assert_eq!(client.try_submit_passkey_attestation(&alice, &payload, &a, &c, &sig, &key), invalid);
+
+    // Registration ceremony instead of an assertion
+    let (a, c, sig) = passkey_assertion(&env, &signer, &payload, "webauthn.create", 0x05);
+    assert_eq!(client.try_submit_passkey_attestation(&alice, &payload, &a, &c, &sig, &key), invalid);
+
+    // User not present
+    let (a, c, sig) = passkey_assertion(&env, &signer, &payload, "webauthn.get", 0x04);
+    assert_eq!(client.try_submit_passkey_attestation(&alice, &payload, &a, &c, &sig, &key), invalid);
+
+    // Truncated authenticator data, oversized client data
+    let (a, c, sig) = passkey_assertion(&env, &signer, &payload, "webauthn.get", 0x05);
+    let short = a.slice(..33);
+    assert_eq!(client.try_submit_passkey_attestation(&alice, &payload, &short, &c, &sig, &key), invalid);
+    let mut long = c.clone();
+    long.extend_from_slice(&[b' '; 1024]);
+    assert_eq!(client.try_submit_passkey_attestation(&alice, &payload, &a, &long, &sig, &key), invalid);
+
+    // Raw signature over the payload, or over other authenticator data
+    let raw = signer.sign(&env, &payload);
+    assert!(client.try_submit_passkey_attestation(&alice, &payload, &a, &c, &raw, &key).is_err());
+    let mut tampered = a.clone();
+    tampered.set(36, 2);
+    assert!(client.try_submit_passkey_attestation(&alice, &payload, &tampered, &c, &sig, &key).is_err());
+
+    // Ed25519 keys do not sign WebAuthn assertions
+    let ed_key = TestSigner::ed25519(1).register(&env, &client, &issuer);
+    assert_eq!(client.try_submit_passkey_attestation(&alice, &payload, &a, &c, &sig, &ed_key), invalid);
+
+    assert!(!client.is_nonce_used(&issuer, &1));
+    assert_eq!(client.submit_passkey_attestation(&alice, &payload, &a, &c, &sig, &key), 0);
+    assert_eq!(client.get_verifications(&alice).get(0).unwrap().points, 15);
+}
+
+#[test]
+fn test_issuer_key_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let signer = TestSigner::secp256r1(1);
+    let public_key = signer.public_key(&env);
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_add_issuer_key(&stranger, &KeyAlgorithm::Secp256r1, &public_key),
+        Err(Ok(PassportError::IssuerNotRegistered.into()))
+    );
+
+    let key = signer.register(&env, &client, &issuer);
+    assert_eq!(signer.register(&env, &client, &issuer), key);
+    assert_eq!(client.issuer_keys(&issuer), vec![&env, key.clone()]);
+    assert_eq!(
+        client.get_issuer_key(&key),
+        Some(IssuerKeyRecord {
+            issuer: issuer.clone(),
+            algorithm: KeyAlgorithm::Secp256r1,
+            public_key: public_key.clone(),
+        })
+    );
+
+    let other = Address::generate(&env);
+    client.add_issuer(&other);
+    assert_eq!(
+        client.try_add_issuer_key(&other, &KeyAlgorithm::Secp256r1, &public_key),
+        Err(Ok(PassportError::KeyAlreadyRegistered.into()))
+    );
+
+    // Keys must have the size of their algorithm; secp256r1 ones uncompressed
+    let ed_key = TestSigner::ed25519(1).public_key(&env);
+    assert_eq!(
+        client.try_add_issuer_key(&issuer, &KeyAlgorithm::Secp256r1, &ed_key),
+        Err(Ok(PassportError::InvalidPublicKey.into()))
+    );
+    assert_eq!(
+        client.try_add_issuer_key(&issuer, &KeyAlgorithm::Ed25519, &public_key),
+        Err(Ok(PassportError::InvalidPublicKey.into()))
+    );
+    let TestSigner::Secp256r1(k) = &signer else { unreachable!() };
+    let compressed = Bytes::from_slice(&env, k.verifying_key().to_encoded_point(true).as_bytes());
+    assert_eq!(
+        client.try_add_issuer_key(&issuer, &KeyAlgorithm::Secp256r1, &compressed),
+        Err(Ok(PassportError::InvalidPublicKey.into()))
+    );
+
+    client.remove_issuer_key(&key);
+    assert_eq!(last_event(&env), Event::IssuerKeyRemoved(issuer.clone(), key.clone()));
+    assert_eq!(client.issuer_keys(&issuer).len(), 0);
+    assert_eq!(
+        client.try_remove_issuer_key(&key),
+        Err(Ok(PassportError::UnknownIssuerKey.into()))
+    );
+
+    // Removing the issuer drops its keys but keeps its used nonces
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    signer.register(&env, &client, &issuer);
+    let attestation = Attestation {
+        contract: client.address.clone(),
+        wallet: alice.clone(),
+        vtype: VerificationType::Over18,
+        points: 10,
+        expires_at: None,
+        nonce: 0,
+    };
+    let (payload, signature) = sign_attestation(&env, &signer, &attestation);
+    client.submit_attestation(&alice, &payload, &signature, &key);
+
+    client.remove_issuer(&issuer);
+    assert_eq!(client.issuer_keys(&issuer).len(), 0);
+    assert_eq!(
+        client.try_submit_attestation(&alice, &payload, &signature, &key),
+        Err(Ok(PassportError::UnknownIssuerKey.into()))
+    );
+
+    client.add_issuer(&issuer);
+    client.grant_scope(&issuer, &VerificationType::Over18);
+    signer.register(&env, &client, &issuer);
+    assert!(client.is_nonce_used(&issuer, &0));
+    assert_eq!(
+        client.try_submit_attestation(&alice, &payload, &signature, &key),
+        Err(Ok(PassportError::InvalidNonce.into()))
+    );
+}
+
+#[test]
+fn test_renew_verification() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &Some(2_000));
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    env.ledger().set_timestamp(2_500);
+    assert_eq!(client.get_score(&alice), 0);
+
+    assert_eq!(
+        client.renew_verification(&issuer, &alice, &VerificationType::Over18, &Some(10_000)),
+        10
+    );
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationRenewed(alice.clone(), VerificationType::Over18, Some(10_000))
+    );
+    let verif = client.get_verifications(&alice).get(0).unwrap();
+    assert_eq!(verif.timestamp, 2_500);
+    assert_eq!(verif.expires_at, Some(10_000));
+    assert_eq!(verif.status, Status::Approved);
+    assert_eq!(client.get_score(&alice), 10);
+
+    assert_eq!(
+        client.try_renew_verification(&issuer, &alice, &VerificationType::GitHub, &None),
+        Err(Ok(PassportError::VerificationNotFound.into()))
+    );
+}
+
+#[test]
+fn test_get_expiring() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &Some(1_100));
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &10, &Some(5_000));
+    client.upsert_verification(&issuer, &alice, &VerificationType::Twitter, &10, &None);
+
+    assert_eq!(client.get_expiring(&alice, &50).len(), 0);
+    let expiring = client.get_expiring(&alice, &100);
+    assert_eq!(expiring.len(), 1);
+    assert_eq!(expiring.get(0).unwrap().vtype, VerificationType::Over18);
+
+    // Already expired ones are included
+    env.ledger().set_timestamp(2_000);
+    assert_eq!(client.get_expiring(&alice, &0).len(), 1);
+    assert_eq!(client.get_expiring(&alice, &u64::MAX).len(), 2);
+}
+
+#[test]
+fn test_verification_timestamp_accuracy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    
+    let before = env.ledger().timestamp();
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    let after = env.ledger().timestamp();
+    
+    let verifs = client.get_verifications(&alice);
+    let verif = &verifs.get(0).unwrap();
+    
+    // Timestamp should be within the expected range
+    assert!(verif.timestamp >= before);
+    assert!(verif.timestamp <= after);
+}
+
+#[test]
+fn test_pause_stops_writes_but_not_reads() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let name = String::from_str(&env, "Alice");
+    let surnames = String::from_str(&env, "Doe");
+    client.register(&alice, &name, &surnames);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    client.pause();
+    assert_eq!(last_event(&env), Event::Paused);
+    assert!(client.is_paused(&PauseSwitch::Registration));
+
+    let paused = Ok(PassportError::Paused.into());
+    assert_eq!(
+        client.try_register(&Address::generate(&env), &name, &surnames).unwrap_err(),
+        paused
+    );
+    assert_eq!(
+        client.try_upsert_verification(&issuer, &alice, &VerificationType::GitHub, &10, &None).unwrap_err(),
+        paused
+    );
+    assert_eq!(
+        client.try_renew_verification(&issuer, &alice, &VerificationType::Over18, &None).unwrap_err(),
+        paused
+    );
+    assert_eq!(
+        client.try_flag_wallet(&issuer, &alice, &symbol_short!("sybil"), &5).unwrap_err(),
+        paused
+    );
+    assert_eq!(client.try_update_profile(&alice, &name, &surnames).unwrap_err(), paused);
+
+    // Reads keep working
+    assert_eq!(client.get_score(&alice), 10);
+    assert_eq!(client.get_verifications(&alice).len(), 1);
+
+    // Revoking is how a compromised issuer is cleaned up, so it stays open
+    client.revoke_verification(&issuer, &alice, &VerificationType::Over18, &symbol_short!("leak"));
+    assert_eq!(client.get_score(&alice), 0);
+
+    client.unpause();
+    assert_eq!(last_event(&env), Event::Unpaused);
+    assert!(!client.is_paused(&PauseSwitch::Registration));
+    client.update_profile(&alice, &String::from_str(&env, "Alicia"), &surnames);
+}
+
+#[test]
+fn test_pause_switches_are_independent() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    let name = String::from_str(&env, "Alice");
+    let surnames = String::from_str(&env, "Doe");
+    client.register(&alice, &name, &surnames);
+
+    client.set_paused(&PauseSwitch::VerificationWrites, &true);
+    assert_eq!(
+        last_event(&env),
+        Event::PauseSwitchSet(PauseSwitch::VerificationWrites, true)
+    );
+    assert!(client.is_paused(&PauseSwitch::VerificationWrites));
+    assert!(!client.is_paused(&PauseSwitch::Registration));
+
+    assert_eq!(
+        client.try_upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None),
+        Err(Ok(PassportError::Paused.into()))
+    );
+    client.register(&Address::generate(&env), &name, &surnames);
+    client.update_profile(&alice, &String::from_str(&env, "Alicia"), &surnames);
+
+    // Lifting the global pause does not clear a switch
+    client.pause();
+    client.unpause();
+    assert!(client.is_paused(&PauseSwitch::VerificationWrites));
+
+    client.set_paused(&PauseSwitch::VerificationWrites, &false);
+    assert_eq!(
+        last_event(&env),
+        Event::PauseSwitchSet(PauseSwitch::VerificationWrites, false)
+    );
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+}
+
+#[test]
+#[should_panic]
+fn test_pause_requires_admin_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(StellarPassport, (&admin,));
+    let client = StellarPassportClient::new(&env, &contract_id);
+
+    client.pause();
+}
+
+#[test]
+fn test_get_verification_lookup() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert_eq!(client.get_verification(&alice, &VerificationType::GitHub), None);
+
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &15, &None);
+    let github = client.get_verification(&alice, &VerificationType::GitHub).unwrap();
+    assert_eq!(github.points, 15);
+    assert_eq!(github.status, Status::Pending);
+    assert_eq!(client.get_verification(&alice, &VerificationType::Twitter), None);
+
+    assert_eq!(
+        client.try_get_verification(&Address::generate(&env), &VerificationType::GitHub),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_get_verifications_page() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    // Empty passport
+    assert_eq!(client.get_verifications_page(&alice, &0, &10, &None).len(), 0);
+
+    let types = [
+        VerificationType::Over18,
+        VerificationType::Twitter,
+        VerificationType::GitHub,
+        VerificationType::BrightID,
+        VerificationType::WorldID,
+    ];
+    for vtype in types.iter() {
+        client.upsert_verification(&issuer, &alice, vtype, &10, &None);
+    }
+
+    let first = client.get_verifications_page(&alice, &0, &2, &None);
+    assert_eq!(first.len(), 2);
+    assert_eq!(first.get(0).unwrap().vtype, VerificationType::Over18);
+    assert_eq!(first.get(1).unwrap().vtype, VerificationType::Twitter);
+
+    // Last page is short, and pages past the end are empty
+    let last = client.get_verifications_page(&alice, &4, &2, &None);
+    assert_eq!(last.len(), 1);
+    assert_eq!(last.get(0).unwrap().vtype, VerificationType::WorldID);
+    assert_eq!(client.get_verifications_page(&alice, &5, &2, &None).len(), 0);
+    assert_eq!(client.get_verifications_page(&alice, &u32::MAX, &2, &None).len(), 0);
+    assert_eq!(client.get_verifications_page(&alice, &0, &0, &None).len(), 0);
+    assert_eq!(
+        client.get_verifications_page(&alice, &0, &u32::MAX, &None),
+        client.get_verifications(&alice)
+    );
+
+    assert_eq!(
+        client.try_get_verifications_page(&Address::generate(&env), &0, &10, &None),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_get_verifications_page_filters_by_status() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    for vtype in [
+        VerificationType::Over18,
+        VerificationType::Twitter,
+        VerificationType::GitHub,
+        VerificationType::BrightID,
+    ] {
+        client.upsert_verification(&issuer, &alice, &vtype, &10, &None);
+    }
+    client.approve_verification(&issuer, &alice, &VerificationType::Twitter);
+    client.approve_verification(&issuer, &alice, &VerificationType::BrightID);
+
+    // Positions count only the matching verifications
+    let approved = Some(Status::Approved);
+    let page = client.get_verifications_page(&alice, &1, &10, &approved);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().vtype, VerificationType::BrightID);
+
+    let pending = client.get_verifications_page(&alice, &0, &10, &Some(Status::Pending));
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending.get(0).unwrap().vtype, VerificationType::Over18);
+    assert_eq!(pending.get(1).unwrap().vtype, VerificationType::GitHub);
+
+    assert_eq!(client.get_verifications_page(&alice, &0, &10, &Some(Status::Revoked)).len(), 0);
+}
+
+#[test]
+fn test_quorum_approves_once_reached() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let kyc = VerificationType::WorldID;
+    let second = Address::generate(&env);
+    let third = Address::generate(&env);
+    for other in [&second, &third] {
+        client.add_issuer(other);
+        client.grant_scope(other, &kyc);
+    }
+
+    assert_eq!(client.get_quorum(&kyc), 1);
+    client.set_quorum(&kyc, &3);
+    assert_eq!(last_event(&env), Event::QuorumSet(kyc.clone(), 3));
+    assert_eq!(client.get_quorum(&kyc), 3);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    assert_eq!(client.upsert_verification(&issuer, &alice, &kyc, &20, &None), 0);
+    // Confirming twice does not count twice
+    assert_eq!(client.upsert_verification(&issuer, &alice, &kyc, &20, &None), 0);
+    assert_eq!(client.upsert_verification(&second, &alice, &kyc, &20, &None), 0);
+    let verif = client.get_verification(&alice, &kyc).unwrap();
+    assert_eq!(verif.status, Status::Pending);
+    assert_eq!(verif.attesters, vec![&env, issuer.clone(), second.clone()]);
+
+    // A reviewer cannot approve it before the quorum either
+    assert_eq!(
+        client.try_approve_verification(&issuer, &alice, &kyc),
+        Err(Ok(PassportError::QuorumNotReached.into()))
+    );
+
+    assert_eq!(client.upsert_verification(&third, &alice, &kyc, &20, &None), 20);
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationStatusChanged(
+            alice.clone(),
+            kyc.clone(),
+            Status::Pending,
+            Status::Approved
+        )
+    );
+    let verif = client.get_verification(&alice, &kyc).unwrap();
+    assert_eq!(verif.status, Status::Approved);
+    assert_eq!(verif.attesters.len(), 3);
+    assert_eq!(client.get_score(&alice), 20);
+}
+
+#[test]
+fn test_quorum_reached_by_renewal() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let kyc = VerificationType::WorldID;
+    let second = Address::generate(&env);
+    client.add_issuer(&second);
+    client.grant_scope(&second, &kyc);
+    client.set_quorum(&kyc, &2);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert_eq!(client.upsert_verification(&issuer, &alice, &kyc, &20, &None), 0);
+    // Renewing its own record does not count twice
+    assert_eq!(client.renew_verification(&issuer, &alice, &kyc, &None), 0);
+    assert_eq!(client.get_verification(&alice, &kyc).unwrap().status, Status::Pending);
+
+    // A second issuer renewing it confirms it
+    assert_eq!(client.renew_verification(&second, &alice, &kyc, &None), 20);
+    assert_eq!(
+        last_event(&env),
+        Event::VerificationStatusChanged(
+            alice.clone(),
+            kyc.clone(),
+            Status::Pending,
+            Status::Approved
+        )
+    );
+    let verif = client.get_verification(&alice, &kyc).unwrap();
+    assert_eq!(verif.status, Status::Approved);
+    assert_eq!(verif.attesters, vec![&env, issuer.clone(), second.clone()]);
+    assert_eq!(client.get_raw_score(&alice), 20);
+    let history = client.get_history(&alice, &0, &10);
+    assert_eq!(history.get(2).unwrap().action, HistoryAction::StatusChanged);
+    assert_eq!(history.get(2).unwrap().actor, second);
+}
+
+#[test]
+fn test_quorum_restarts_after_rejection() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let kyc = VerificationType::WorldID;
+    let second = Address::generate(&env);
+    client.add_issuer(&second);
+    client.grant_scope(&second, &kyc);
+    client.set_quorum(&kyc, &2);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &kyc, &20, &None);
+    client.reject_verification(&second, &alice, &kyc);
+
+    // Re-submitting starts collecting confirmations again
+    client.upsert_verification(&second, &alice, &kyc, &20, &None);
+    let verif = client.get_verification(&alice, &kyc).unwrap();
+    assert_eq!(verif.status, Status::Pending);
+    assert_eq!(verif.attesters, vec![&env, second.clone()]);
+
+    assert_eq!(client.upsert_verification(&issuer, &alice, &kyc, &20, &None), 20);
+    // After a reset, issuers that already confirmed do not re-approve it by
+    // writing again
+    client.reset_to_pending(&issuer, &alice, &kyc);
+    assert_eq!(client.upsert_verification(&second, &alice, &kyc, &20, &None), 0);
+    assert_eq!(client.renew_verification(&issuer, &alice, &kyc, &None), 0);
+    assert_eq!(client.get_verification(&alice, &kyc).unwrap().status, Status::Pending);
+    // A reviewer can, since the confirmations are kept
+    assert_eq!(client.approve_verification(&issuer, &alice, &kyc), 20);
+}
+
+#[test]
+fn test_quorum_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let kyc = VerificationType::WorldID;
+
+    let invalid = Err(Ok(PassportError::InvalidConfig.into()));
+    assert_eq!(client.try_set_quorum(&kyc, &0), invalid);
+    assert_eq!(client.try_set_quorum(&kyc, &11), invalid);
+
+    // Without a quorum, one issuer and the usual review
+    client.set_quorum(&kyc, &2);
+    client.set_quorum(&kyc, &1);
+    assert_eq!(client.get_quorum(&kyc), 1);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    client.upsert_verification(&issuer, &alice, &kyc, &20, &None);
+    assert_eq!(client.approve_verification(&issuer, &alice, &kyc), 20);
+}
+
+#[test]
+fn test_history_records_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+    let reviewer = Address::generate(&env);
+    client.add_issuer(&reviewer);
+    client.grant_scope(&reviewer, &VerificationType::WorldID);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert_eq!(client.get_history(&alice, &0, &10).len(), 0);
+
+    let kyc = VerificationType::WorldID;
+    env.ledger().set_timestamp(100);
+    client.upsert_verification(&issuer, &alice, &kyc, &10, &None);
+    env.ledger().set_timestamp(200);
+    client.upsert_verification(&issuer, &alice, &kyc, &15, &Some(10_000));
+    env.ledger().set_timestamp(300);
+    client.approve_verification(&reviewer, &alice, &kyc);
+    client.renew_verification(&issuer, &alice, &kyc, &Some(20_000));
+    client.revoke_verification(&issuer, &alice, &kyc, &symbol_short!("fraud"));
+    client.upsert_verification(&issuer, &alice, &kyc, &15, &None);
+
+    let history = client.get_history(&alice, &0, &10);
+    let actions: Vec<HistoryAction> = Vec::from_iter(&env, history.iter().map(|e| e.action));
+    assert_eq!(
+        actions,
+        vec![
+            &env,
+            HistoryAction::Created,
+            HistoryAction::Updated,
+            HistoryAction::StatusChanged,
+            HistoryAction::Renewed,
+            HistoryAction::Revoked,
+            HistoryAction::Created,
+        ]
+    );
+
+    // The previous values are kept
+    let update = history.get(1).unwrap();
+    assert_eq!((update.seq, update.points, update.expires_at), (1, 15, Some(10_000)));
+    assert_eq!(history.get(0).unwrap().points, 10);
+
+    // When was the KYC approved and by whom
+    let approval = history.get(2).unwrap();
+    assert_eq!(approval.status, Status::Approved);
+    assert_eq!(approval.actor, reviewer);
+    assert_eq!(approval.timestamp, 300);
+    assert_eq!(history.get(4).unwrap().status, Status::Revoked);
+
+    let page = client.get_history(&alice, &4, &10);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().seq, 4);
+
+    // Point table changes apply when scoring and rewrite no record, so the
+    // last entry still matches what is stored
+    client.set_point_rule(&kyc, &PointRule::Fixed(5));
+    client.recompute_score(&alice);
+    assert_eq!(client.get_history(&alice, &0, &10).len(), 6);
+    let last = client.get_history(&alice, &5, &1).get(0).unwrap();
+    assert_eq!(last.points, client.get_verification(&alice, &kyc).unwrap().points);
+
+    assert_eq!(
+        client.try_get_history(&Address::generate(&env), &0, &10),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_history_prunes_oldest_entries() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    for points in 1..=105 {
+        client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &points, &None);
+    }
+
+    // Only the last 100 are kept; earlier positions start at the oldest one
+    let oldest = client.get_history(&alice, &0, &2);
+    assert_eq!(oldest.get(0).unwrap().seq, 5);
+    assert_eq!(oldest.get(0).unwrap().points, 6);
+    assert_eq!(client.get_history(&alice, &0, &u32::MAX).len(), 100);
+
+    let last = client.get_history(&alice, &104, &10);
+    assert_eq!(last.len(), 1);
+    assert_eq!(last.get(0).unwrap().points, 105);
+    assert_eq!(client.get_history(&alice, &105, &10).len(), 0);
+    assert_eq!(client.get_history(&alice, &50, &0).len(), 0);
+}
+
+#[test]
+fn test_score_at_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    env.ledger().set_sequence_number(10);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    env.ledger().set_sequence_number(20);
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &10, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::Over18);
+
+    // Several writes in one ledger: the final score counts
+    env.ledger().set_sequence_number(30);
+    client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &5, &None);
+    client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+    client.revoke_verification(&issuer, &alice, &VerificationType::Over18, &symbol_short!("fraud"));
+
+    assert_eq!(client.get_score_at(&alice, &10), 0);
+    assert_eq!(client.get_score_at(&alice, &19), 0);
+    assert_eq!(client.get_score_at(&alice, &20), 10);
+    assert_eq!(client.get_score_at(&alice, &29), 10);
+    assert_eq!(client.get_score_at(&alice, &30), 5);
+    assert_eq!(client.get_score_at(&alice, &u32::MAX), 5);
+
+    // Nothing is known before registration
+    assert_eq!(
+        client.try_get_score_at(&alice, &9),
+        Err(Ok(PassportError::CheckpointUnavailable.into()))
+    );
+    assert_eq!(
+        client.try_get_score_at(&Address::generate(&env), &30),
+        Err(Ok(PassportError::NotRegistered.into()))
+    );
+}
+
+#[test]
+fn test_score_checkpoints_are_bounded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, issuer) = setup(&env);
+
+    let alice = Address::generate(&env);
+    env.ledger().set_sequence_number(5);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    // Score `i` from ledger `10 * i`
+    for i in 1..=150 {
+        env.ledger().set_sequence_number(10 * i as u32);
+        client.upsert_verification(&issuer, &alice, &VerificationType::GitHub, &i, &None);
+        if i == 1 {
+            client.approve_verification(&issuer, &alice, &VerificationType::GitHub);
+        }
+    }
+
+    // The last 100 checkpoints are kept: from ledger 510 on
+    for ledger in (510..1_600).step_by(7) {
+        assert_eq!(client.get_score_at(&alice, &ledger), (ledger / 10).min(150) as i32);
+    }
+    assert_eq!(client.get_score_at(&alice, &510), 51);
+    assert_eq!(
+        client.try_get_score_at(&alice, &509),
+        Err(Ok(PassportError::CheckpointUnavailable.into()))
+    );
+}
+
+#[test]
+fn test_soulbound_token_interface() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+    let token = soroban_sdk::token::TokenClient::new(&env, &client.address);
+
+    assert_eq!(token.name(), String::from_str(&env, "Stellar Passport"));
+    assert_eq!(token.symbol(), String::from_str(&env, "PASSPORT"));
+    assert_eq!(token.decimals(), 0);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    assert_eq!(token.balance(&alice), 0);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    assert_eq!(token.balance(&alice), 1);
+    assert_eq!(token.allowance(&alice, &bob), 0);
+
+    // Identity cannot be traded
+    let non_transferable = Err(Ok(PassportError::NonTransferable.into()));
+    assert_eq!(token.try_transfer(&alice, &bob, &1), non_transferable);
+    assert_eq!(token.try_transfer_from(&bob, &alice, &bob, &1), non_transferable);
+    assert_eq!(token.try_approve(&alice, &bob, &1, &1_000), non_transferable);
+    assert_eq!(token.try_burn(&alice, &1), non_transferable);
+    assert_eq!(token.try_burn_from(&bob, &alice, &1), non_transferable);
+    assert_eq!(token.balance(&alice), 1);
 }
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, String, Symbol};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Map, String, Symbol, Vec};
 
 /// Tipos de verificación soportados.
 /// `Custom(Symbol)` permite extensiones (p.ej. "over18_cr", "kyc_sumsub").
@@ -14,12 +14,75 @@ pub enum VerificationType {
 }
 
 /// Estado de una verificación.
+/// Transiciones válidas: Pending -> Approved | Rejected, Approved -> Rejected | Pending,
+/// Rejected -> Pending (una verificación rechazada debe reenviarse antes de aprobarse).
+/// `Revoked` solo se alcanza con `revoke_verification` y es final salvo reemisión.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum Status {
     Approved,
     Rejected,
     Pending,
+    Revoked,
+}
+
+/// Puntos permitidos para un tipo de verificación en la tabla de puntos.
+/// `Fixed(p)`: exactamente `p`. `Range(min, max)`: entre ambos, inclusive.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PointRule {
+    Fixed(i32),
+    Range(i32, i32),
+}
+
+/// Política de cálculo del score a partir de las verificaciones aprobadas y vigentes.
+/// Por categoría: la verificación de más puntos cuenta entera y cada una de las
+/// siguientes se multiplica una vez más por `decay_bps` (rendimientos decrecientes);
+/// la suma se pondera con `weights` y se limita con `caps`.
+/// Los tipos sin categoría asignada usan la suya propia; todos los `Custom` comparten "custom".
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ScoringPolicy {
+    pub categories: Map<VerificationType, Symbol>,
+    pub weights: Map<Symbol, u32>, // bps por categoría (10_000 = 1x); por defecto 1x
+    pub caps: Map<Symbol, i32>,    // máximo por categoría, tras ponderar
+    pub decay_bps: u32,            // 10_000 = sin rendimientos decrecientes
+}
+
+/// Nivel de reputación (p.ej. "bronze", "silver", "gold").
+/// Se alcanza con un score efectivo mayor o igual a `min_score`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Tier {
+    pub name: Symbol,
+    pub min_score: i32,
+}
+
+/// Expresión de requisitos evaluada por `meets_requirements`.
+/// `And` / `Or` necesitan al menos un hijo y `Not` exactamente uno; la
+/// profundidad y el número de nodos están acotados.
+/// Las hojas ignoran verificaciones revocadas.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Requirement {
+    And(Vec<Requirement>),
+    Or(Vec<Requirement>),
+    Not(Vec<Requirement>),
+    HasType(VerificationType),              // existe, en cualquier otro estado
+    Approved(VerificationType),             // aprobada y no caducada
+    NotExpired(VerificationType),           // existe y no ha caducado
+    IssuedBy(VerificationType, Address),    // emitida por ese emisor
+    MinScore(i32),                          // score efectivo >= valor
+    NoActiveFlags,                          // sin marcas activas
+}
+
+/// Interruptores de pausa por grupo de funciones (además de la pausa global).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PauseSwitch {
+    Registration,       // register
+    VerificationWrites, // upsert, atestaciones (también passkey), renovaciones, revisiones y marcas
+    ProfileUpdates,     // update_profile
 }
 
 /// Una verificación concreta aplicada a un usuario.
@@ -29,8 +92,115 @@ pub struct Verification {
     pub vtype: VerificationType,
     pub points: i32,
     pub timestamp: u64,     // epoch seconds (host now)
-    pub issuer: Address,    // quién la emite (puede ser el propio usuario o un verificador)
+    pub expires_at: Option<u64>, // epoch seconds; None = no caduca
+    pub issuer: Address,    // emisor de confianza registrado que la emite
     pub status: Status,     // estado de la verificación
+    pub attesters: Vec<Address>, // emisores que han confirmado el registro vigente
+}
+
+/// Agregado del score de una wallet, para calcularlo sin leer cada verificación.
+/// Cubre las verificaciones aprobadas y vigentes: las que no decaen, sumadas por
+/// categoría con los puntos ajustados a la tabla; las que decaen, por tipo, para
+/// leerlas al calcular.
+/// Deja de valer al cambiar la tabla, las vidas medias o las categorías
+/// (`epoch`) y cuando llega `next_expiry`; entonces se recalcula.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ScoreTotals {
+    pub epoch: u32,               // ScoreEpoch con el que se calculó
+    pub sums: Map<Symbol, i64>,   // puntos por categoría, antes de pesos y topes
+    pub decaying: Vec<VerificationType>,
+    pub next_expiry: Option<u64>, // primera caducidad entre las incluidas
+}
+
+/// Revocación registrada por el emisor de una verificación.
+/// `reason` es un código corto (p.ej. "fraud", "compromised").
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Revocation {
+    pub issuer: Address,
+    pub reason: Symbol,
+    pub timestamp: u64,     // epoch seconds (host now)
+}
+
+/// Algoritmo de firma de una clave de emisor.
+/// `Secp256r1`: claves P-256 que firman el sha256 del payload (`submit_attestation`)
+/// o passkeys de dispositivos móviles con aserciones WebAuthn (`submit_passkey_attestation`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum KeyAlgorithm {
+    Ed25519,
+    Secp256r1,
+}
+
+/// Clave de firma registrada para un emisor.
+/// `public_key`: 32 bytes (ed25519) o 65 bytes SEC1 sin comprimir (secp256r1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct IssuerKeyRecord {
+    pub issuer: Address,
+    pub algorithm: KeyAlgorithm,
+    pub public_key: Bytes,
+}
+
+/// Atestación firmada off-chain por la clave de un emisor.
+/// El payload de `submit_attestation` es esta estructura serializada en XDR;
+/// `contract` evita que la firma se reutilice en otro despliegue.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Attestation {
+    pub contract: Address,
+    pub wallet: Address,
+    pub vtype: VerificationType,
+    pub points: i32,
+    pub expires_at: Option<u64>,
+    pub nonce: u64,         // único por emisor, en cualquier orden
+}
+
+/// Marca negativa (fraude, reporte sybil...) emitida por un emisor de confianza.
+/// Resta `penalty` del score mientras esté activa; un emisor tiene como mucho
+/// una marca por `reason` en cada wallet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Flag {
+    pub issuer: Address,
+    pub reason: Symbol,
+    pub penalty: i32,       // puntos a restar (> 0)
+    pub timestamp: u64,     // epoch seconds (host now)
+}
+
+/// Tipo de cambio registrado en el historial de verificaciones.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum HistoryAction {
+    Created,       // alta o re-emisión tras rechazo/revocación
+    Updated,       // nuevos puntos o caducidad sobre el registro vigente
+    StatusChanged, // revisión o quórum alcanzado
+    Renewed,
+    Revoked,
+}
+
+/// Entrada del historial de una wallet: estado de la verificación tras el
+/// cambio y quién lo hizo. `seq` crece siempre, aunque se poden las antiguas.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct HistoryEntry {
+    pub seq: u32,
+    pub vtype: VerificationType,
+    pub action: HistoryAction,
+    pub actor: Address,     // emisor o revisor que hizo el cambio
+    pub points: i32,
+    pub status: Status,
+    pub expires_at: Option<u64>,
+    pub timestamp: u64,     // epoch seconds (host now)
+}
+
+/// Score de una wallet a partir de un ledger, hasta el siguiente checkpoint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Checkpoint {
+    pub ledger: u32,        // secuencia del ledger en que se escribió
+    pub score: i32,
 }
 
 /// Datos agregados del usuario.
@@ -45,12 +215,55 @@ pub struct User {
     pub ver_count: u32,
 }
 
+/// Umbral y extensión de TTL (en ledgers) para las entradas del contrato.
+/// Cuando el TTL restante baja de `threshold` se extiende hasta `extend_to`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TtlConfig {
+    pub threshold: u32,
+    pub extend_to: u32,
+}
+
 /// Claves de almacenamiento del contrato.
+/// Configuración en instance storage; datos de usuario en persistent storage.
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
+    // instance
+    Admin,
+    PendingAdmin,           // Address propuesta, pendiente de aceptar
+    TtlConfig,
+    Version,                // u32: versión del esquema de almacenamiento
+    WalletCount,            // u32: wallets en el índice de registro
+    MigrationCursor,        // u32: siguiente posición del índice a migrar
+    Issuer(Address),        // bool: emisor de confianza registrado
+    IssuerScope(Address, VerificationType), // bool: el emisor puede escribir ese tipo
+    IssuerScopes(Address),  // Vec<VerificationType> concedidos al emisor
+    DefaultValidity(VerificationType), // u64: segundos de validez por defecto
+    Quorum(VerificationType), // u32: emisores distintos necesarios para aprobar
+    PointTable,             // Map<VerificationType, PointRule>
+    ScoringPolicy,          // ScoringPolicy vigente
+    HalfLives,              // Map<VerificationType, u64>: vida media en segundos
+    Tiers,                  // Vec<Tier> con umbrales estrictamente crecientes
+    Paused,                 // bool: pausa global
+    PauseSwitch(PauseSwitch), // bool: grupo de funciones pausado
+    IssuerKey(BytesN<32>),  // IssuerKeyRecord por identificador de clave
+    IssuerKeys(Address),    // Vec<BytesN<32>>: identificadores de las claves del emisor
+    ScoreEpoch,             // u32: cambia con la tabla, las vidas medias o las categorías
+    // persistent
+    Wallet(u32),            // Address en esa posición del índice de registro
     User(Address),
-    Verifications(Address), // Vec<Verification>
+    Verifications(Address), // Vec<Verification> (hasta el esquema 3)
+    Verification(Address, VerificationType), // Verification de ese tipo
+    VerificationTypes(Address), // Vec<VerificationType>: índice de tipos del usuario
+    ScoreTotals(Address),   // ScoreTotals de la wallet
+    Revocation(Address, VerificationType), // Revocation vigente de ese tipo
+    Flags(Address),         // Vec<Flag> activas
+    HistoryLen(Address),    // u32: entradas de historial escritas (siguiente seq)
+    History(Address, u32),  // HistoryEntry en la posición seq % MAX_HISTORY_PER_USER
+    CheckpointLen(Address), // u32: checkpoints de score escritos
+    Checkpoint(Address, u32), // Checkpoint en la posición seq % MAX_CHECKPOINTS_PER_USER
+    UsedNonces(Address, u64), // u64: bits de los nonces usados por el emisor, 64 por entrada
 }
 
 /// Eventos de negocio (útiles para indexadores y backends).
@@ -59,4 +272,31 @@ pub enum DataKey {
 pub enum Event {
     UserRegistered(Address),
     VerificationUpserted(Address, VerificationType, i32, i32, i32),
+    IssuerAdded(Address),
+    IssuerRemoved(Address),
+    ScopeGranted(Address, VerificationType),
+    ScopeRevoked(Address, VerificationType),
+    Initialized(Address),                 // admin inicial
+    AdminProposed(Address, Address),      // admin actual, propuesto
+    AdminChanged(Address, Address),       // admin anterior, nuevo
+    VerificationStatusChanged(Address, VerificationType, Status, Status), // wallet, tipo, anterior, nuevo
+    Migrated(u32, u32),                   // versión de esquema anterior, nueva
+    Upgraded(BytesN<32>),                 // hash del nuevo WASM
+    VerificationRenewed(Address, VerificationType, Option<u64>), // wallet, tipo, nueva caducidad
+    VerificationRevoked(Address, VerificationType, Address, Symbol), // wallet, tipo, emisor, motivo
+    IssuerKeyAdded(Address, BytesN<32>),   // emisor, identificador de clave
+    IssuerKeyRemoved(Address, BytesN<32>), // emisor, identificador de clave
+    PointRuleSet(VerificationType, PointRule),
+    PointRuleRemoved(VerificationType),
+    ScoreRecomputed(Address, i32, i32),   // wallet, score de la última escritura, actual
+    ScoringPolicySet(ScoringPolicy),
+    HalfLifeSet(VerificationType, u64),   // tipo, vida media (0 = sin decaimiento)
+    TiersSet(Vec<Tier>),
+    TierChanged(Address, Symbol, Symbol), // wallet, nivel anterior, nuevo
+    WalletFlagged(Address, Address, Symbol, i32), // wallet, emisor, motivo, penalización
+    FlagLifted(Address, Address, Symbol, Address), // wallet, emisor, motivo, quién la levanta
+    QuorumSet(VerificationType, u32),     // tipo, emisores necesarios (1 = sin quórum)
+    Paused,                               // pausa global
+    Unpaused,
+    PauseSwitchSet(PauseSwitch, bool),    // grupo, pausado
 }
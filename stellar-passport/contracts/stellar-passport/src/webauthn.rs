@@ -0,0 +1,79 @@
+use soroban_sdk::{panic_with_error, Bytes, BytesN, Env};
+
+use crate::errors::PassportError;
+
+// Authenticator data starts with the rpId hash (32 bytes), the flags (1) and
+// the signature counter (4).
+const AUTH_DATA_MIN_LEN: u32 = 37;
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+// Browsers send a few hundred bytes; anything much larger is not a client
+// data JSON worth scanning.
+pub const MAX_CLIENT_DATA_LEN: u32 = 1024;
+
+const TYPE_GET: &[u8] = b"\"type\":\"webauthn.get\"";
+const CHALLENGE_KEY: &[u8] = b"\"challenge\":\"";
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Unpadded base64url, as WebAuthn encodes the challenge.
+fn base64url_32(bytes: &[u8; 32]) -> [u8; 43] {
+    let mut out = [0u8; 43];
+    let mut pos = 0;
+    for chunk in bytes.chunks(3) {
+        let mut n = 0u32;
+        for (i, b) in chunk.iter().enumerate() {
+            n |= (*b as u32) << (16 - 8 * i);
+        }
+        for i in 0..=chunk.len() {
+            out[pos] = BASE64URL[(n >> (18 - 6 * i)) as usize & 63];
+            pos += 1;
+        }
+    }
+    out
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Checks a passkey assertion over `payload`: the client data must be a
+/// `webauthn.get` whose challenge is the sha256 of the payload, the user must
+/// have been present, and the signature must cover
+/// `authenticator_data || sha256(client_data_json)`. The rpId and origin are
+/// not checked; the issuer key registration is what binds the passkey.
+pub fn verify_assertion(
+    env: &Env,
+    public_key: &BytesN<65>,
+    payload: &Bytes,
+    authenticator_data: &Bytes,
+    client_data_json: &Bytes,
+    signature: &BytesN<64>,
+) {
+    let flags = authenticator_data.get(32).unwrap_or(0);
+    if authenticator_data.len() < AUTH_DATA_MIN_LEN || flags & FLAG_USER_PRESENT == 0 {
+        panic_with_error!(env, PassportError::InvalidAttestation);
+    }
+
+    let len = client_data_json.len();
+    if len > MAX_CLIENT_DATA_LEN {
+        panic_with_error!(env, PassportError::InvalidAttestation);
+    }
+    let mut buf = [0u8; MAX_CLIENT_DATA_LEN as usize];
+    let json = &mut buf[..len as usize];
+    client_data_json.copy_into_slice(json);
+
+    let mut challenge = [0u8; CHALLENGE_KEY.len() + 44];
+    challenge[..CHALLENGE_KEY.len()].copy_from_slice(CHALLENGE_KEY);
+    let digest = env.crypto().sha256(payload).to_array();
+    challenge[CHALLENGE_KEY.len()..CHALLENGE_KEY.len() + 43].copy_from_slice(&base64url_32(&digest));
+    challenge[CHALLENGE_KEY.len() + 43] = b'"';
+    if !contains(json, TYPE_GET) || !contains(json, &challenge) {
+        panic_with_error!(env, PassportError::InvalidAttestation);
+    }
+
+    let mut signed = authenticator_data.clone();
+    signed.extend_from_array(&env.crypto().sha256(client_data_json).to_array());
+    env.crypto()
+        .secp256r1_verify(public_key, &env.crypto().sha256(&signed), signature);
+}
@@ -0,0 +1,236 @@
+//! Integration tests running the contract as WASM, the way it is deployed.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use soroban_sdk::{testutils::Address as _, Address, Env, String, Symbol};
+use stellar_passport::errors::PassportError;
+use stellar_passport::types::{DataKey, VerificationType};
+use stellar_passport::StellarPassportClient;
+
+// Release build of contract version 2 (schema 2), used as the source of
+// upgrades. `make fixtures` rebuilds it from the commit that added it.
+mod passport_v2 {
+    soroban_sdk::contractimport!(file = "tests/fixtures/stellar_passport_v2.wasm");
+}
+
+// The current tree as WASM: the build at `PASSPORT_WASM` when set (`make
+// test` passes the one from `stellar contract build`), otherwise built once
+// per test run, in its own target dir so it does not contend with `cargo test`.
+fn current_wasm() -> &'static [u8] {
+    static WASM: OnceLock<std::vec::Vec<u8>> = OnceLock::new();
+    WASM.get_or_init(|| {
+        if let Some(path) = std::env::var_os("PASSPORT_WASM") {
+            return std::fs::read(path).expect("cannot read PASSPORT_WASM");
+        }
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = manifest_dir.join("../../target/upgrade-test");
+        let status = Command::new(env!("CARGO"))
+            .args(["build", "--release", "--target", "wasm32v1-none"])
+            .arg("--manifest-path")
+            .arg(manifest_dir.join("Cargo.toml"))
+            .env("CARGO_TARGET_DIR", &target_dir)
+            .status()
+            .expect("failed to run cargo");
+        assert!(
+            status.success(),
+            "building the contract to WASM failed (is the wasm32v1-none target installed?)"
+        );
+        std::fs::read(target_dir.join("wasm32v1-none/release/stellar_passport.wasm")).unwrap()
+    })
+}
+
+fn deploy(env: &Env, wasm: &[u8]) -> Address {
+    let admin = Address::generate(env);
+    env.register(wasm, (&admin,))
+}
+
+#[test]
+fn upgrade_from_previous_release_keeps_passports() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = deploy(&env, passport_v2::WASM);
+    let old_client = passport_v2::Client::new(&env, &contract_id);
+    assert_eq!(old_client.version(), 2);
+    assert_eq!(old_client.schema_version(), 2);
+
+    let issuer = Address::generate(&env);
+    let over18 = passport_v2::VerificationType::Over18;
+    old_client.add_issuer(&issuer);
+    old_client.grant_scope(&issuer, &over18);
+
+    let alice = Address::generate(&env);
+    old_client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+    old_client.upsert_verification(&issuer, &alice, &over18, &10);
+    old_client.approve_verification(&issuer, &alice, &over18);
+    // That release accepted wallets already in the index
+    old_client.index_wallets(&soroban_sdk::vec![&env, alice.clone()]);
+
+    let new_hash = env.deployer().upload_contract_wasm(current_wasm());
+    old_client.upgrade(&new_hash);
+
+    // New code on the old data: the schema only moves with `migrate`
+    let client = StellarPassportClient::new(&env, &contract_id);
+    assert!(client.version() > 2);
+    assert_eq!(client.schema_version(), 2);
+    assert_eq!(
+        client.try_get_score(&alice).unwrap_err(),
+        Ok(PassportError::MigrationRequired.into())
+    );
+
+    // Bring the stored data to the layout of the new build
+    while client.migrate(&10) > 0 {}
+    assert!(client.schema_version() > 2);
+
+    assert_eq!(client.get_score(&alice), 10);
+    let verifs = client.get_verifications(&alice);
+    assert_eq!(verifs.len(), 1);
+    // Fields added after schema 2 get their defaults
+    let verif = verifs.get(0).unwrap();
+    assert_eq!(verif.expires_at, None);
+    assert_eq!(verif.attesters, soroban_sdk::vec![&env, issuer.clone()]);
+    assert!(client.is_issuer(&issuer));
+
+    // The upgraded contract keeps accepting writes
+    client.upsert_verification(&issuer, &alice, &VerificationType::Over18, &20, &None);
+    assert_eq!(client.get_score(&alice), 20);
+}
+
+#[test]
+fn upgrade_requires_admin() {
+    let env = Env::default();
+    let client = passport_v2::Client::new(&env, &deploy(&env, passport_v2::WASM));
+
+    let new_hash = env.deployer().upload_contract_wasm(current_wasm());
+    assert!(client.try_upgrade(&new_hash).is_err());
+}
+
+#[test]
+fn refuses_to_operate_on_newer_schema() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = StellarPassportClient::new(&env, &deploy(&env, current_wasm()));
+
+    let alice = Address::generate(&env);
+    client.register(&alice, &String::from_str(&env, "Alice"), &String::from_str(&env, "Doe"));
+
+    // Storage migrated by a newer release, then the code was rolled back
+    let newer = client.schema_version() + 1;
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&DataKey::Version, &newer);
+    });
+
+    let too_new = Ok(PassportError::SchemaTooNew.into());
+    assert_eq!(client.try_get_score(&alice).unwrap_err(), too_new);
+    assert_eq!(
+        client
+            .try_register(
+                &Address::generate(&env),
+                &String::from_str(&env, "Bob"),
+                &String::from_str(&env, "Doe")
+            )
+            .unwrap_err(),
+        too_new
+    );
+    assert_eq!(client.try_add_issuer(&Address::generate(&env)).unwrap_err(), too_new);
+    assert_eq!(client.try_migrate(&10).unwrap_err(), too_new);
+
+    // The admin can still move the code forward
+    let hash = env.deployer().upload_contract_wasm(current_wasm());
+    client.upgrade(&hash);
+}
+
+// CPU instructions, memory and ledger write bytes of re-issuing one
+// verification on a passport that holds `held` of them, pending or approved
+// (approved ones count towards the score the write recomputes). The issuer is
+// granted `MAX_HELD` scopes and a second passport holds the other `MAX_HELD -
+// held` verifications, so the instance storage and the ledger the test host
+// keeps in memory are the same size either way.
+type UpsertCost = (i64, i64, u32);
+
+const MAX_HELD: u32 = 40;
+
+fn upsert_cost_v2(held: u32, approved: bool) -> UpsertCost {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = passport_v2::Client::new(&env, &deploy(&env, passport_v2::WASM));
+    let issuer = Address::generate(&env);
+    client.add_issuer(&issuer);
+
+    for i in 0..MAX_HELD {
+        let vtype = passport_v2::VerificationType::Custom(Symbol::new(&env, &std::format!("c{i}")));
+        client.grant_scope(&issuer, &vtype);
+    }
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    for (wallet, count) in [(&alice, held), (&bob, MAX_HELD - held)] {
+        client.register(wallet, &String::from_str(&env, "Jane"), &String::from_str(&env, "Doe"));
+        for i in 0..count {
+            let vtype = passport_v2::VerificationType::Custom(Symbol::new(&env, &std::format!("c{i}")));
+            client.upsert_verification(&issuer, wallet, &vtype, &10);
+            if approved {
+                client.approve_verification(&issuer, wallet, &vtype);
+            }
+        }
+    }
+
+    let vtype = passport_v2::VerificationType::Custom(Symbol::new(&env, "c0"));
+    client.upsert_verification(&issuer, &alice, &vtype, &20);
+    let res = env.cost_estimate().resources();
+    (res.instructions, res.mem_bytes, res.write_bytes)
+}
+
+fn upsert_cost_current(held: u32, approved: bool) -> UpsertCost {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = StellarPassportClient::new(&env, &deploy(&env, current_wasm()));
+    let issuer = Address::generate(&env);
+    client.add_issuer(&issuer);
+
+    for i in 0..MAX_HELD {
+        let vtype = VerificationType::Custom(Symbol::new(&env, &std::format!("c{i}")));
+        client.grant_scope(&issuer, &vtype);
+    }
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    for (wallet, count) in [(&alice, held), (&bob, MAX_HELD - held)] {
+        client.register(wallet, &String::from_str(&env, "Jane"), &String::from_str(&env, "Doe"));
+        for i in 0..count {
+            let vtype = VerificationType::Custom(Symbol::new(&env, &std::format!("c{i}")));
+            client.upsert_verification(&issuer, wallet, &vtype, &10, &None);
+            if approved {
+                client.approve_verification(&issuer, wallet, &vtype);
+            }
+        }
+    }
+
+    let vtype = VerificationType::Custom(Symbol::new(&env, "c0"));
+    client.upsert_verification(&issuer, &alice, &vtype, &20, &None);
+    let res = env.cost_estimate().resources();
+    (res.instructions, res.mem_bytes, res.write_bytes)
+}
+
+// Release 2 rewrote the whole `Vec<Verification>` on every upsert; each type
+// now has its own entry and the upsert reads and writes only that one, plus
+// the wallet's score totals, whose size does not depend on the verifications.
+#[test]
+fn upsert_cost_before_and_after_per_type_entries() {
+    for approved in [false, true] {
+        let (_, _, before_one) = upsert_cost_v2(1, approved);
+        let (_, _, before_many) = upsert_cost_v2(MAX_HELD, approved);
+        let (cpu_one, mem_one, after_one) = upsert_cost_current(1, approved);
+        let (cpu_many, mem_many, after_many) = upsert_cost_current(MAX_HELD, approved);
+
+        // Ledger writes no longer grow with the number of verifications
+        assert!(before_many > before_one * 4);
+        assert_eq!(after_many, after_one, "approved: {approved}");
+        assert!(after_many < before_many);
+
+        // Neither do CPU and memory, within 10%
+        assert!(cpu_many * 10 <= cpu_one * 11, "approved: {approved}, cpu {cpu_one} -> {cpu_many}");
+        assert!(mem_many * 10 <= mem_one * 11, "approved: {approved}, mem {mem_one} -> {mem_many}");
+    }
+}